@@ -0,0 +1,44 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum Error {
+    Io { path: String, source: io::Error },
+    Parse { path: String, line: Option<usize>, message: String },
+    Image { path: String, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn io(path: &str, source: io::Error) -> Self {
+        Error::Io { path: String::from(path), source }
+    }
+
+    pub fn parse(path: &str, line: Option<usize>, message: String) -> Self {
+        Error::Parse { path: String::from(path), line, message }
+    }
+
+    pub fn image(path: &str, message: String) -> Self {
+        Error::Image { path: String::from(path), message }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::Parse { path, line: Some(line), message } => write!(f, "{}:{}: {}", path, line, message),
+            Error::Parse { path, line: None, message } => write!(f, "{}: {}", path, message),
+            Error::Image { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { .. } | Error::Image { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::fs;
+use crate::error::{Error, Result};
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+const VERSION: u32 = 2;
+const PIXEL_TYPE_FLOAT: i32 = 2;
+
+fn write_attribute(bytes: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    bytes.extend_from_slice(name.as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(kind.as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    bytes.extend_from_slice(value);
+}
+
+fn box2i(width: u32, height: u32) -> Vec<u8> {
+    [0, 0, width as i32 - 1, height as i32 - 1].iter().flat_map(|value: &i32| value.to_le_bytes().to_vec()).collect()
+}
+
+// uncompressed scanline openexr file with float channels, every channel is a width * height plane.
+// Names with dots such as "albedo.R" are read as layers by compositing applications.
+pub fn save_exr(path: &str, width: u32, height: u32, channels: &[(String, &[f32])]) -> Result<()> {
+    let pixels = (width * height) as usize;
+
+    if let Some((name, _)) = channels.iter().find(|(_, values)| values.len() != pixels) {
+        return Err(Error::image(path, format!("channel \"{}\" doesn't match image size", name)));
+    }
+
+    // channel list has to be sorted by name and scanlines store channels in the same order
+    let mut channels: Vec<&(String, &[f32])> = channels.iter().collect();
+    channels.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut channel_list = Vec::new();
+
+    for (name, _) in &channels {
+        channel_list.extend_from_slice(name.as_bytes());
+        channel_list.push(0);
+        channel_list.extend_from_slice(&PIXEL_TYPE_FLOAT.to_le_bytes());
+        channel_list.extend_from_slice(&[0, 0, 0, 0]);
+        channel_list.extend_from_slice(&1i32.to_le_bytes());
+        channel_list.extend_from_slice(&1i32.to_le_bytes());
+    }
+
+    channel_list.push(0);
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+
+    write_attribute(&mut bytes, "channels", "chlist", &channel_list);
+    write_attribute(&mut bytes, "compression", "compression", &[0]);
+    write_attribute(&mut bytes, "dataWindow", "box2i", &box2i(width, height));
+    write_attribute(&mut bytes, "displayWindow", "box2i", &box2i(width, height));
+    write_attribute(&mut bytes, "lineOrder", "lineOrder", &[0]);
+    write_attribute(&mut bytes, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+    write_attribute(&mut bytes, "screenWindowCenter", "v2f", &[0; 8]);
+    write_attribute(&mut bytes, "screenWindowWidth", "float", &1f32.to_le_bytes());
+    bytes.push(0);
+
+    // offset table points at every scanline block
+    let line_size = 8 + channels.len() * width as usize * 4;
+    let table_end = bytes.len() + height as usize * 8;
+
+    for y in 0..height as usize {
+        bytes.extend_from_slice(&((table_end + y * line_size) as u64).to_le_bytes());
+    }
+
+    for y in 0..height as usize {
+        bytes.extend_from_slice(&(y as i32).to_le_bytes());
+        bytes.extend_from_slice(&((line_size - 8) as i32).to_le_bytes());
+
+        for (_, values) in &channels {
+            for value in &values[y * width as usize..(y + 1) * width as usize] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+
+    fs::write(path, bytes).map_err(|e| Error::io(path, e))
+}
@@ -0,0 +1,249 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::collections::HashMap;
+use gltf;
+use gltf::khr_lights_punctual::Kind;
+use gltf::mesh::Mode;
+use crate::renderables::mesh::{Mesh, MeshOptions};
+use crate::renderables::material::Material;
+use crate::renderables::triangle::Triangle;
+use crate::renderables::texture::Texture;
+use crate::renderer::node::Node;
+use crate::renderer::light::Light;
+use crate::renderer::camera::Camera;
+use crate::math::vec3::Vector3;
+use crate::math::quaternion::Quaternion;
+use crate::math::color::Color;
+use crate::error::{Error, Result};
+
+struct GltfData<'a> {
+    path: &'a str,
+    options: &'a MeshOptions,
+    buffers: Vec<gltf::buffer::Data>,
+    textures: Vec<Arc<Texture>>,
+    meshes: HashMap<usize, Vec<Arc<Mesh>>>,
+}
+
+pub fn load_gltf(path: &str) -> Result<Vec<Node>> {
+    load_gltf_with_options(path, &MeshOptions::new())
+}
+
+pub fn load_gltf_with_options(path: &str, options: &MeshOptions) -> Result<Vec<Node>> {
+    let (document, buffers, images) = match gltf::import(Path::new(path)) {
+        Ok(import) => import,
+        Err(gltf::Error::Io(error)) => return Err(Error::io(path, error)),
+        Err(error) => return Err(Error::parse(path, None, error.to_string()))
+    };
+
+    let textures = images.iter().map(|image| Arc::new(create_texture(image))).collect();
+
+    let mut data = GltfData {
+        path,
+        options,
+        buffers,
+        textures,
+        meshes: HashMap::new(),
+    };
+
+    let scene = match document.default_scene().or_else(|| document.scenes().next()) {
+        Some(scene) => scene,
+        None => return Err(Error::parse(path, None, String::from("file contains no scenes")))
+    };
+
+    scene.nodes().map(|node| create_node(&node, &mut data)).collect()
+}
+
+fn create_texture(image: &gltf::image::Data) -> Texture {
+    use gltf::image::Format;
+
+    // (channels, bytes per channel, is bgr order)
+    let (channels, channel_size, bgr) = match image.format {
+        Format::R8 => (1, 1, false),
+        Format::R8G8 => (2, 1, false),
+        Format::R8G8B8 => (3, 1, false),
+        Format::R8G8B8A8 => (4, 1, false),
+        Format::B8G8R8 => (3, 1, true),
+        Format::B8G8R8A8 => (4, 1, true),
+        Format::R16 => (1, 2, false),
+        Format::R16G16 => (2, 2, false),
+        Format::R16G16B16 => (3, 2, false),
+        Format::R16G16B16A16 => (4, 2, false),
+    };
+
+    let mut pixels = Vec::with_capacity((image.width * image.height) as usize);
+
+    for pixel in image.pixels.chunks(channels * channel_size) {
+        // for 16 bit channels only the most significant byte is kept
+        let channel = |i: usize| pixel[i * channel_size + channel_size - 1] as f32;
+
+        let color = match (channels, bgr) {
+            (1, _) | (2, _) => Color::new(channel(0), channel(0), channel(0)),
+            (_, true) => Color::new(channel(2), channel(1), channel(0)),
+            _ => Color::new(channel(0), channel(1), channel(2))
+        };
+
+        pixels.push(color);
+    }
+
+    Texture::new(image.width, image.height, pixels)
+}
+
+fn create_node(gltf_node: &gltf::Node, data: &mut GltfData) -> Result<Node> {
+    let name = match gltf_node.name() {
+        Some(name) => String::from(name),
+        None => format!("node_{}", gltf_node.index())
+    };
+
+    let mut node = Node::new(name);
+
+    let (translation, rotation, scale) = gltf_node.transform().decomposed();
+    node.translation = Vector3::new(translation[0], translation[1], translation[2]);
+    node.rotation = Quaternion::new(rotation[0], rotation[1], rotation[2], rotation[3]);
+    node.scale = Vector3::new(scale[0], scale[1], scale[2]);
+
+    if let Some(gltf_mesh) = gltf_node.mesh() {
+        for (i, mesh) in get_meshes(&gltf_mesh, data)?.iter().enumerate() {
+            let mut primitive_node = Node::new(format!("{}_primitive_{}", node.name, i));
+            primitive_node.renderable = Some(Arc::clone(mesh) as Arc<_>);
+            node.add_child(primitive_node);
+        }
+    }
+
+    if let Some(gltf_light) = gltf_node.light() {
+        node.light = Some(create_light(&gltf_light));
+    }
+
+    if let Some(gltf_camera) = gltf_node.camera() {
+        if let gltf::camera::Projection::Perspective(perspective) = gltf_camera.projection() {
+            // gltf cameras sit at origin of their local space looking down -z, position is given relative to target
+            let target = Vector3::new(0., 0., -1.);
+            node.camera = Some(Camera::new(perspective.yfov().to_degrees(), &Vector3::new(0., 0., 0.) - &target, target));
+        }
+    }
+
+    for child in gltf_node.children() {
+        node.add_child(create_node(&child, data)?);
+    }
+
+    Ok(node)
+}
+
+fn create_light(gltf_light: &gltf::khr_lights_punctual::Light) -> Light {
+    let [r, g, b] = gltf_light.color();
+    let color = Color::new(r * 255., g * 255., b * 255.);
+    let intensity = gltf_light.intensity();
+
+    match gltf_light.kind() {
+        Kind::Directional => Light::new_directional(color, intensity, Vector3::new(0., 0., 1.), 0.),
+        // spot cone is not supported, such lights are treated as point ones
+        Kind::Point | Kind::Spot { .. } => Light::new(color, intensity, Vector3::new(0., 0., 0.))
+    }
+}
+
+// vertex indices of triangles, None for points and lines which have no surface to render
+fn get_triangle_indices(mode: Mode, indices: &[usize]) -> Option<Vec<[usize; 3]>> {
+    let count = indices.len();
+
+    match mode {
+        Mode::Triangles => Some(indices.chunks_exact(3).map(|face| [face[0], face[1], face[2]]).collect()),
+        // every other triangle of strip is flipped to keep winding
+        Mode::TriangleStrip => Some((0..count.saturating_sub(2)).map(|i| if i % 2 == 0 {
+            [indices[i], indices[i + 1], indices[i + 2]]
+        } else {
+            [indices[i + 1], indices[i], indices[i + 2]]
+        }).collect()),
+        Mode::TriangleFan => Some((1..count.saturating_sub(1)).map(|i| [indices[0], indices[i], indices[i + 1]]).collect()),
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => None
+    }
+}
+
+fn get_meshes(gltf_mesh: &gltf::Mesh, data: &mut GltfData) -> Result<Vec<Arc<Mesh>>> {
+    if let Some(meshes) = data.meshes.get(&gltf_mesh.index()) {
+        return Ok(meshes.clone());
+    }
+
+    let mut meshes = Vec::new();
+
+    for (i, primitive) in gltf_mesh.primitives().enumerate() {
+        let buffers = &data.buffers;
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+        let positions: Vec<Vector3<f32>> = match reader.read_positions() {
+            Some(positions) => positions.map(|p| Vector3::new(p[0], p[1], p[2])).collect(),
+            None => continue
+        };
+
+        let uvs: Vec<(f32, f32)> = match reader.read_tex_coords(0) {
+            Some(uvs) => uvs.into_f32().map(|uv| (uv[0], uv[1])).collect(),
+            None => vec![(0., 0.); positions.len()]
+        };
+
+        let normals: Option<Vec<Vector3<f32>>> = reader.read_normals().map(|normals| normals.map(|n| Vector3::new(n[0], n[1], n[2])).collect());
+
+        let indices: Vec<usize> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().map(|index| index as usize).collect(),
+            None => (0..positions.len()).collect()
+        };
+
+        let faces = match get_triangle_indices(primitive.mode(), &indices) {
+            Some(faces) => faces,
+            None => continue
+        };
+
+        let vertex_count = usize::min(usize::min(positions.len(), uvs.len()), normals.as_ref().map_or(usize::MAX, |normals| normals.len()));
+
+        if let Some(index) = indices.iter().find(|index| **index >= vertex_count) {
+            return Err(Error::parse(data.path, None, format!("primitive {} of mesh {} references vertex {} of {}", i, gltf_mesh.index(), index, vertex_count)));
+        }
+
+        let triangles: Vec<Triangle> = faces.iter().map(|[index1, index2, index3]| {
+            let mut triangle = Triangle::with_uvs(
+                positions[*index1],
+                positions[*index2],
+                positions[*index3],
+                [uvs[*index1], uvs[*index2], uvs[*index3]]
+            );
+
+            if let Some(normals) = &normals {
+                triangle.set_normals([normals[*index1], normals[*index2], normals[*index3]]);
+            }
+
+            triangle
+        }).collect();
+
+        let name = match gltf_mesh.name() {
+            Some(name) => format!("{}_{}", name, i),
+            None => format!("mesh_{}_{}", gltf_mesh.index(), i)
+        };
+
+        let material = create_material(&primitive.material(), &data.textures);
+        let mut mesh = Mesh::new(material, triangles, name);
+        mesh.process(data.options);
+
+        meshes.push(Arc::new(mesh));
+    }
+
+    data.meshes.insert(gltf_mesh.index(), meshes.clone());
+
+    Ok(meshes)
+}
+
+fn create_material(gltf_material: &gltf::Material, textures: &[Arc<Texture>]) -> Material {
+    let pbr = gltf_material.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    let [er, eg, eb] = gltf_material.emissive_factor();
+
+    let mut material = Material::new();
+    material.diffuse_color = Color::new(r * 255., g * 255., b * 255.);
+    material.emissive_color = Color::new(er * 255., eg * 255., eb * 255.);
+    material.opacity = a;
+    material.metallic = pbr.metallic_factor();
+    material.roughness = pbr.roughness_factor();
+    material.reflectivity = material.metallic * (1. - material.roughness);
+
+    if let Some(info) = pbr.base_color_texture() {
+        material.diffuse_texture = Some(Arc::clone(&textures[info.texture().source().index()]));
+    }
+
+    material
+}
@@ -1,2 +1,6 @@
 pub mod display;
 pub mod obj_loader;
+pub mod gltf_loader;
+pub mod ply_loader;
+pub mod stl_loader;
+pub mod exr_writer;
@@ -1,3 +1,4 @@
+use std::fs;
 use std::path::Path;
 use tobj;
 use crate::renderables::mesh::Mesh;
@@ -5,32 +6,137 @@ use crate::renderables::material::Material;
 use crate::math::vec3::Vector3;
 use crate::renderables::triangle::Triangle;
 use crate::math::color::Color;
+use crate::error::{Error, Result};
 
+// tobj doesn't report where parsing failed, so look for the first malformed line of the failed kind
+fn find_error_line(path: &str, error: &tobj::LoadError) -> Option<usize> {
+    let (prefix, components) = match error {
+        tobj::LoadError::PositionParseError => ("v", 3),
+        tobj::LoadError::NormalParseError => ("vn", 3),
+        tobj::LoadError::TexcoordParseError => ("vt", 2),
+        tobj::LoadError::FaceParseError => ("f", 3),
+        _ => return None
+    };
 
-pub fn load_obj(path: &str) -> Vec<Mesh> {
-    let model = tobj::load_obj(&Path::new(path));
-    assert!(model.is_ok());
+    let source = fs::read_to_string(path).ok()?;
 
-    let (models, materials) = model.unwrap();
-    let mut meshes: Vec<Mesh> = Vec::new();
+    source.lines().position(|line| {
+        let mut tokens = line.split_whitespace();
 
-    for (_i, m) in models.iter().enumerate() {
-        let mesh = &m.mesh;
+        if tokens.next() != Some(prefix) {
+            return false;
+        }
+
+        let values: Vec<&str> = tokens.collect();
+        let malformed = match prefix {
+            "f" => values.iter().any(|value| value.split('/').next().unwrap_or("").parse::<i64>().is_err()),
+            _ => values.iter().take(components).any(|value| value.parse::<f32>().is_err())
+        };
+
+        values.len() < components || malformed
+    }).map(|index| index + 1)
+}
+
+fn get_param_values(mat: &tobj::Material, name: &str) -> Option<Vec<f32>> {
+    mat.unknown_param.get(name).map(|value| {
+        value.split_whitespace().filter_map(|value| value.parse::<f32>().ok()).collect()
+    })
+}
+
+fn get_param_color(mat: &tobj::Material, name: &str) -> Option<Color> {
+    match get_param_values(mat, name) {
+        Some(ref values) if values.len() >= 3 => Some(Color::new(values[0] * 255., values[1] * 255., values[2] * 255.)),
+        Some(ref values) if values.len() == 1 => Some(Color::new(values[0] * 255., values[0] * 255., values[0] * 255.)),
+        _ => None
+    }
+}
+
+fn get_param_scalar(mat: &tobj::Material, name: &str) -> Option<f32> {
+    get_param_values(mat, name).and_then(|values| values.first().cloned())
+}
+
+fn to_color(values: &[f32; 3]) -> Color {
+    Color::new(values[0] * 255., values[1] * 255., values[2] * 255.)
+}
 
-        let mat = &materials[mesh.material_id.unwrap()];
-        let diffuse_color = Color::new(
-            mat.diffuse[0] * 255.,
-            mat.diffuse[1] * 255.,
-            mat.diffuse[2] * 255.
-        );
+fn max_channel(color: &Color) -> f32 {
+    f32::max(color.r, f32::max(color.g, color.b)) / 255.
+}
 
+fn create_material(mat: &tobj::Material) -> Material {
+    let mut material = Material::new();
+    material.diffuse_color = to_color(&mat.diffuse);
+    material.ambient_color = to_color(&mat.ambient);
+    material.specular_color = to_color(&mat.specular);
+    material.shininess = mat.shininess;
+    material.opacity = mat.dissolve;
+    material.refractive_index = mat.optical_density;
+
+    if let Some(emissive_color) = get_param_color(mat, "Ke") {
+        material.emissive_color = emissive_color;
+    }
 
+    // specular exponent is converted to roughness unless PBR roughness is given, tobj reads missing
+    // exponent as zero and such materials keep the default mirror roughness
+    if let Some(roughness) = get_param_scalar(mat, "Pr") {
+        material.roughness = roughness;
+    } else if mat.shininess > 0. {
+        material.roughness = f32::sqrt(2. / (mat.shininess + 2.));
+    }
 
-        let mut material = Material::new();
-        material.diffuse_color = diffuse_color;
-        material.shininess = mat.shininess;
-        material.opacity = mat.dissolve;
+    material.sheen = get_param_scalar(mat, "Ps").unwrap_or(0.);
+    material.anisotropy = get_param_scalar(mat, "aniso").unwrap_or(0.);
+    material.anisotropy_rotation = get_param_scalar(mat, "anisor").unwrap_or(0.);
+
+    if let Some(transmission_color) = get_param_color(mat, "Tf") {
+        material.transmission_color = transmission_color;
+    }
 
+    // models without highlights keep default reflectivity
+    if let Some(illumination_model) = mat.illumination_model {
+        material.illumination_model = illumination_model;
+
+        match illumination_model {
+            0 | 1 => {},
+            2 => material.reflectivity = max_channel(&material.specular_color) * (1. - material.roughness),
+            _ => material.reflectivity = max_channel(&material.specular_color)
+        }
+
+        // refraction models describe dielectrics, light that is not reflected gets transmitted
+        if let 6 | 7 = illumination_model {
+            material.opacity = 0.;
+        }
+    }
+
+    if let Some(metallic) = get_param_scalar(mat, "Pm") {
+        material.metallic = metallic;
+        material.reflectivity = f32::max(material.reflectivity, metallic * (1. - material.roughness));
+    }
+
+    material
+}
+
+pub fn load_obj(path: &str) -> Result<Vec<Mesh>> {
+    if let Err(error) = fs::File::open(path) {
+        return Err(Error::io(path, error));
+    }
+
+    let (models, materials) = match tobj::load_obj(Path::new(path)) {
+        Ok(model) => model,
+        Err(error) => return Err(Error::parse(path, find_error_line(path, &error), error.to_string()))
+    };
+
+    let mut meshes: Vec<Mesh> = Vec::new();
+
+    for m in models.iter() {
+        let mesh = &m.mesh;
+
+        let material = match mesh.material_id.and_then(|id| materials.get(id)) {
+            Some(mat) => create_material(mat),
+            None => Material::new()
+        };
+
+        let vertices_count = mesh.positions.len() / 3;
         let mut triangles: Vec<Triangle> = Vec::new();
 
         for f in 0..mesh.indices.len() / 3 {
@@ -38,6 +144,10 @@ pub fn load_obj(path: &str) -> Vec<Mesh> {
             let index2 = mesh.indices[3 * f + 1] as usize;
             let index3 = mesh.indices[3 * f + 2] as usize;
 
+            if index1 >= vertices_count || index2 >= vertices_count || index3 >= vertices_count {
+                return Err(Error::parse(path, None, format!("face {} of \"{}\" references missing vertex", f, m.name)));
+            }
+
             let v0 = Vector3::new(
                 mesh.positions[3 * index1],
                 mesh.positions[3 * index1 + 1],
@@ -63,5 +173,5 @@ pub fn load_obj(path: &str) -> Vec<Mesh> {
         meshes.push(Mesh::new(material, triangles, m.name.clone()));
     }
 
-    meshes
+    Ok(meshes)
 }
@@ -0,0 +1,284 @@
+use std::fs;
+use std::path::Path;
+use crate::renderables::mesh::Mesh;
+use crate::renderables::material::Material;
+use crate::renderables::triangle::Triangle;
+use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+use crate::error::{Error, Result};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian
+}
+
+#[derive(Clone, Copy)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64
+}
+
+enum PropertyType {
+    Scalar(ScalarType),
+    List(ScalarType, ScalarType)
+}
+
+struct Property {
+    name: String,
+    property_type: PropertyType
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>
+}
+
+struct Reader<'a> {
+    format: Format,
+    bytes: &'a [u8],
+    offset: usize
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> std::result::Result<ScalarType, String> {
+        let scalar_type = match name {
+            "char" | "int8" => ScalarType::Int8,
+            "uchar" | "uint8" => ScalarType::UInt8,
+            "short" | "int16" => ScalarType::Int16,
+            "ushort" | "uint16" => ScalarType::UInt16,
+            "int" | "int32" => ScalarType::Int32,
+            "uint" | "uint32" => ScalarType::UInt32,
+            "float" | "float32" => ScalarType::Float32,
+            "double" | "float64" => ScalarType::Float64,
+            _ => return Err(format!("unknown property type \"{}\"", name))
+        };
+
+        Ok(scalar_type)
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8
+        }
+    }
+}
+
+impl<'a> Reader<'a> {
+    fn next_token(&mut self) -> &'a str {
+        while self.offset < self.bytes.len() && self.bytes[self.offset].is_ascii_whitespace() {
+            self.offset += 1;
+        }
+
+        let start = self.offset;
+        while self.offset < self.bytes.len() && !self.bytes[self.offset].is_ascii_whitespace() {
+            self.offset += 1;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.offset]).unwrap_or("<binary data>")
+    }
+
+    // line of the current position for ascii files, used for error reporting
+    fn get_line(&self) -> Option<usize> {
+        match self.format {
+            Format::Ascii => Some(self.bytes[..self.offset].iter().filter(|byte| **byte == b'\n').count() + 1),
+            _ => None
+        }
+    }
+
+    fn read(&mut self, scalar_type: ScalarType) -> std::result::Result<f64, String> {
+        if self.format == Format::Ascii {
+            let token = self.next_token();
+
+            return match token.parse::<f64>() {
+                Ok(value) => Ok(value),
+                Err(_) if token.is_empty() => Err(String::from("unexpected end of file")),
+                Err(_) => Err(format!("invalid number \"{}\"", token))
+            };
+        }
+
+        let size = scalar_type.size();
+
+        if self.offset + size > self.bytes.len() {
+            return Err(format!("unexpected end of file at byte {}", self.offset));
+        }
+
+        let mut raw = [0u8; 8];
+        raw[..size].copy_from_slice(&self.bytes[self.offset..self.offset + size]);
+        self.offset += size;
+
+        if self.format == Format::BinaryBigEndian {
+            raw[..size].reverse();
+        }
+
+        let value = match scalar_type {
+            ScalarType::Int8 => raw[0] as i8 as f64,
+            ScalarType::UInt8 => raw[0] as f64,
+            ScalarType::Int16 => i16::from_le_bytes([raw[0], raw[1]]) as f64,
+            ScalarType::UInt16 => u16::from_le_bytes([raw[0], raw[1]]) as f64,
+            ScalarType::Int32 => i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+            ScalarType::UInt32 => u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+            ScalarType::Float32 => f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+            ScalarType::Float64 => f64::from_le_bytes(raw)
+        };
+
+        Ok(value)
+    }
+}
+
+fn parse_header(path: &str, bytes: &[u8]) -> Result<(Format, Vec<Element>, usize)> {
+    if !bytes.starts_with(b"ply") {
+        return Err(Error::parse(path, Some(1), String::from("missing \"ply\" magic number")));
+    }
+
+    let header_end = b"end_header";
+    let end = match bytes.windows(header_end.len()).position(|window| window == header_end) {
+        Some(end) => end,
+        None => return Err(Error::parse(path, None, String::from("header is not terminated with \"end_header\"")))
+    };
+
+    let mut body_start = end + header_end.len();
+    while body_start < bytes.len() && bytes[body_start] != b'\n' {
+        body_start += 1;
+    }
+    body_start += 1;
+
+    let header = String::from_utf8_lossy(&bytes[..end]);
+    let mut format = Format::Ascii;
+    let mut elements: Vec<Element> = Vec::new();
+
+    for (line_index, line) in header.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let error = |message: String| Error::parse(path, Some(line_index + 1), message);
+
+        match tokens.as_slice() {
+            ["format", "ascii", ..] => format = Format::Ascii,
+            ["format", "binary_little_endian", ..] => format = Format::BinaryLittleEndian,
+            ["format", "binary_big_endian", ..] => format = Format::BinaryBigEndian,
+            ["element", name, count] => elements.push(Element {
+                name: String::from(*name),
+                count: count.parse().map_err(|_| error(format!("invalid element count \"{}\"", count)))?,
+                properties: Vec::new()
+            }),
+            ["property", ..] if elements.is_empty() => return Err(error(String::from("property declared before any element"))),
+            ["property", "list", count_type, item_type, name] => elements.last_mut().unwrap().properties.push(Property {
+                name: String::from(*name),
+                property_type: PropertyType::List(
+                    ScalarType::parse(count_type).map_err(error)?,
+                    ScalarType::parse(item_type).map_err(error)?
+                )
+            }),
+            ["property", scalar_type, name] => elements.last_mut().unwrap().properties.push(Property {
+                name: String::from(*name),
+                property_type: PropertyType::Scalar(ScalarType::parse(scalar_type).map_err(error)?)
+            }),
+            _ => {}
+        }
+    }
+
+    Ok((format, elements, body_start))
+}
+
+pub fn load_ply(path: &str) -> Result<Vec<Mesh>> {
+    let bytes = fs::read(Path::new(path)).map_err(|error| Error::io(path, error))?;
+    let (format, elements, body_start) = parse_header(path, &bytes)?;
+
+    let mut reader = Reader { format, bytes: &bytes, offset: body_start };
+
+    let mut positions: Vec<Vector3<f32>> = Vec::new();
+    let mut colors: Vec<Color> = Vec::new();
+    let mut faces: Vec<Vec<usize>> = Vec::new();
+
+    for element in &elements {
+        for _i in 0..element.count {
+            let mut position = Vector3::new(0., 0., 0.);
+            let mut color = Color::new(255., 255., 255.);
+            let mut has_color = false;
+
+            for property in &element.properties {
+                match &property.property_type {
+                    PropertyType::Scalar(scalar_type) => {
+                        let value = reader.read(*scalar_type)
+                            .map_err(|message| Error::parse(path, reader.get_line(), message))? as f32;
+
+                        // float colors are stored in 0..1 range, integer ones in 0..255
+                        let color_value = match scalar_type {
+                            ScalarType::Float32 | ScalarType::Float64 => value * 255.,
+                            _ => value
+                        };
+
+                        match property.name.as_str() {
+                            "x" => position.x = value,
+                            "y" => position.y = value,
+                            "z" => position.z = value,
+                            "red" | "r" => { color.r = color_value; has_color = true; },
+                            "green" | "g" => { color.g = color_value; has_color = true; },
+                            "blue" | "b" => { color.b = color_value; has_color = true; },
+                            _ => {}
+                        }
+                    },
+                    PropertyType::List(count_type, item_type) => {
+                        let count = reader.read(*count_type)
+                            .map_err(|message| Error::parse(path, reader.get_line(), message))? as usize;
+
+                        // count comes from the file, every item takes at least one of the remaining bytes
+                        let mut items: Vec<usize> = Vec::with_capacity(usize::min(count, bytes.len() - reader.offset));
+                        for _j in 0..count {
+                            let item = reader.read(*item_type)
+                                .map_err(|message| Error::parse(path, reader.get_line(), message))?;
+                            items.push(item as usize);
+                        }
+
+                        if element.name == "face" && (property.name == "vertex_indices" || property.name == "vertex_index") {
+                            faces.push(items);
+                        }
+                    }
+                }
+            }
+
+            if element.name == "vertex" {
+                positions.push(position);
+
+                if has_color {
+                    colors.push(color);
+                }
+            }
+        }
+    }
+
+    let has_colors = colors.len() == positions.len();
+    let mut triangles: Vec<Triangle> = Vec::new();
+
+    for (face_index, face) in faces.iter().enumerate() {
+        if let Some(index) = face.iter().find(|index| **index >= positions.len()) {
+            return Err(Error::parse(path, None, format!("face {} references missing vertex {}", face_index, index)));
+        }
+
+        // polygons are triangulated as a fan around the first vertex
+        for i in 1..face.len().saturating_sub(1) {
+            let indices = [face[0], face[i], face[i + 1]];
+            let mut triangle = Triangle::new(positions[indices[0]], positions[indices[1]], positions[indices[2]]);
+
+            if has_colors {
+                triangle.set_colors([colors[indices[0]], colors[indices[1]], colors[indices[2]]]);
+            }
+
+            triangles.push(triangle);
+        }
+    }
+
+    let name = Path::new(path).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+
+    Ok(vec![Mesh::new(Material::new(), triangles, name)])
+}
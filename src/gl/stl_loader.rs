@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::Path;
+use crate::renderables::mesh::Mesh;
+use crate::renderables::material::Material;
+use crate::renderables::triangle::Triangle;
+use crate::math::vec3::Vector3;
+use crate::error::{Error, Result};
+
+const BINARY_HEADER_SIZE: usize = 84;
+const BINARY_TRIANGLE_SIZE: usize = 50;
+
+fn read_vector(bytes: &[u8]) -> Vector3<f32> {
+    let value = |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+    Vector3::new(value(0), value(4), value(8))
+}
+
+// size of binary file with triangle count declared in its header
+fn get_binary_size(bytes: &[u8]) -> Option<usize> {
+    if bytes.len() < BINARY_HEADER_SIZE {
+        return None;
+    }
+
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+
+    Some(BINARY_HEADER_SIZE + count * BINARY_TRIANGLE_SIZE)
+}
+
+// ascii files start with "solid", some binary exporters write it too so their size has to match exactly
+fn is_binary(path: &str, bytes: &[u8]) -> Result<bool> {
+    let binary_size = get_binary_size(bytes);
+    let has_solid_header = String::from_utf8_lossy(&bytes[..usize::min(bytes.len(), 80)]).trim_start().starts_with("solid");
+
+    match binary_size {
+        Some(size) if size == bytes.len() => Ok(true),
+        _ if has_solid_header => Ok(false),
+        Some(size) => Err(Error::parse(path, None, format!("binary file should have {} bytes, found {}", size, bytes.len()))),
+        None => Err(Error::parse(path, None, format!("binary header is truncated to {} bytes", bytes.len())))
+    }
+}
+
+fn parse_binary(bytes: &[u8]) -> Vec<Triangle> {
+    let mut triangles: Vec<Triangle> = Vec::new();
+
+    for chunk in bytes[BINARY_HEADER_SIZE..].chunks(BINARY_TRIANGLE_SIZE) {
+        // first 12 bytes are facet normal, it is recomputed from vertices
+        triangles.push(Triangle::new(
+            read_vector(&chunk[12..]),
+            read_vector(&chunk[24..]),
+            read_vector(&chunk[36..])
+        ));
+    }
+
+    triangles
+}
+
+fn parse_ascii(path: &str, bytes: &[u8]) -> Result<Vec<Triangle>> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut triangles: Vec<Triangle> = Vec::new();
+    let mut vertices: Vec<Vector3<f32>> = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let error = |message: String| Error::parse(path, Some(line_index + 1), message);
+
+        match tokens.as_slice() {
+            ["vertex", coordinates @ ..] => {
+                let values: Vec<f32> = coordinates.iter().filter_map(|value| value.parse().ok()).collect();
+
+                if coordinates.len() != 3 || values.len() != 3 {
+                    return Err(error(format!("invalid vertex \"{}\"", line.trim())));
+                }
+
+                vertices.push(Vector3::new(values[0], values[1], values[2]));
+            },
+            ["endfacet"] => {
+                if vertices.len() != 3 {
+                    return Err(error(format!("facet has {} vertices instead of 3", vertices.len())));
+                }
+
+                triangles.push(Triangle::new(vertices[0], vertices[1], vertices[2]));
+                vertices.clear();
+            },
+            _ => {}
+        }
+    }
+
+    if triangles.is_empty() {
+        return Err(Error::parse(path, None, String::from("no facets found")));
+    }
+
+    Ok(triangles)
+}
+
+pub fn load_stl(path: &str) -> Result<Vec<Mesh>> {
+    let bytes = fs::read(Path::new(path)).map_err(|error| Error::io(path, error))?;
+
+    let triangles = if is_binary(path, &bytes)? {
+        parse_binary(&bytes)
+    } else {
+        parse_ascii(path, &bytes)?
+    };
+
+    let name = Path::new(path).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+
+    Ok(vec![Mesh::new(Material::new(), triangles, name)])
+}
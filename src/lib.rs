@@ -1,4 +1,5 @@
 #![feature(get_mut_unchecked)]
+pub mod error;
 pub mod gl;
 pub mod math;
 pub mod renderer;
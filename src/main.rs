@@ -16,6 +16,11 @@ use pathtracer::renderables::plane::Plane;
 use pathtracer::math::color::Color;
 
 use pathtracer::renderer::camera_controller::CameraController;
+use pathtracer::renderer::integrator::PathIntegrator;
+use pathtracer::renderer::bidirectional::BidirectionalIntegrator;
+use pathtracer::renderer::photon_mapping::PhotonMapIntegrator;
+use pathtracer::renderer::metropolis::MetropolisIntegrator;
+use pathtracer::renderer::denoiser::Denoiser;
 
 pub fn main() {
     let width = 800;
@@ -25,12 +30,13 @@ pub fn main() {
     let light = Light::new(Color::new(255., 255., 255.), 1.2, Vector3::new(0., 55., 70.));
     scene.add_light(light);
 
-    scene.load_model(String::from("./assets/cornell_box/default.obj"));
+    scene.load_model(String::from("./assets/cornell_box/default.obj")).unwrap();
 
     let camera = Camera::new(65., Vector3::new(-10.,35.,55.), Vector3::new(-10.,40.,-1.));
     let mut camera_controller = CameraController::new(&camera);
 
     let mut renderer = Renderer::new(width, height, camera, scene);
+    renderer.set_progressive(true);
 
     let sdl_context = sdl2::init().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
@@ -50,12 +56,32 @@ pub fn main() {
     let mut display = Display::new(width, height, &texture_creator).unwrap();
     let mut event_pump = sdl_context.event_pump().map_err(|e| e.to_string()).unwrap();
 
+    let mut path_guiding = false;
+    let mut resampled_lighting = false;
+    let mut denoising = false;
+
     'running: loop {
         let now = Instant::now();
         for event in event_pump.poll_iter() {
             match event {
                 Event::Quit { .. } |
                 Event::KeyDown { keycode: Some(Keycode::Escape), .. } => break 'running,
+                Event::KeyDown { keycode: Some(Keycode::Num1), .. } => renderer.set_integrator(Box::new(PathIntegrator::new())),
+                Event::KeyDown { keycode: Some(Keycode::Num2), .. } => renderer.set_integrator(Box::new(BidirectionalIntegrator::new())),
+                Event::KeyDown { keycode: Some(Keycode::Num3), .. } => renderer.set_integrator(Box::new(PhotonMapIntegrator::new(1.))),
+                Event::KeyDown { keycode: Some(Keycode::Num4), .. } => renderer.set_integrator(Box::new(MetropolisIntegrator::new())),
+                Event::KeyDown { keycode: Some(Keycode::G), .. } => {
+                    path_guiding = !path_guiding;
+                    renderer.set_path_guiding(path_guiding);
+                },
+                Event::KeyDown { keycode: Some(Keycode::L), .. } => {
+                    resampled_lighting = !resampled_lighting;
+                    renderer.set_resampled_lighting(resampled_lighting);
+                },
+                Event::KeyDown { keycode: Some(Keycode::D), .. } => {
+                    denoising = !denoising;
+                    renderer.set_denoiser(if denoising { Some(Denoiser::new()) } else { None });
+                },
                 _ => {}
             }
         }
@@ -0,0 +1,171 @@
+use crate::math::vec3::Vector3;
+use crate::math::quaternion::Quaternion;
+use crate::math::mat4::Matrix4;
+use crate::math::color::Color;
+use crate::math::lerp;
+
+pub trait Interpolate {
+    fn interpolate(&self, other: &Self, amount: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &f32, amount: f32) -> f32 {
+        lerp(*self, *other, amount)
+    }
+}
+
+impl Interpolate for Vector3<f32> {
+    fn interpolate(&self, other: &Vector3<f32>, amount: f32) -> Vector3<f32> {
+        Vector3::new(lerp(self.x, other.x, amount), lerp(self.y, other.y, amount), lerp(self.z, other.z, amount))
+    }
+}
+
+impl Interpolate for Quaternion<f32> {
+    fn interpolate(&self, other: &Quaternion<f32>, amount: f32) -> Quaternion<f32> {
+        self.slerp(other, amount)
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Color, amount: f32) -> Color {
+        Color::new(lerp(self.r, other.r, amount), lerp(self.g, other.g, amount), lerp(self.b, other.b, amount))
+    }
+}
+
+// easing applied on the way from keyframe to the next one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Interpolation {
+    pub fn apply(&self, amount: f32) -> f32 {
+        match self {
+            Interpolation::Step => if amount < 1. { 0. } else { 1. },
+            Interpolation::Linear => amount,
+            Interpolation::EaseIn => amount * amount,
+            Interpolation::EaseOut => amount * (2. - amount),
+            Interpolation::EaseInOut => amount * amount * (3. - 2. * amount)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    pub interpolation: Interpolation,
+}
+
+// values held constant before first and after last keyframe
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl <T: Interpolate + Clone> Track<T> {
+    pub fn new() -> Self {
+        Track {
+            keyframes: Vec::new()
+        }
+    }
+
+    // moves from start at time 0 to end at time 1
+    pub fn linear(start: T, end: T) -> Self {
+        let mut track = Track::new();
+        track.add_keyframe(0., start);
+        track.add_keyframe(1., end);
+
+        track
+    }
+
+    pub fn add_keyframe(&mut self, time: f32, value: T) {
+        self.add_keyframe_with_interpolation(time, value, Interpolation::Linear);
+    }
+
+    pub fn add_keyframe_with_interpolation(&mut self, time: f32, value: T, interpolation: Interpolation) {
+        let index = self.keyframes.iter().position(|keyframe| keyframe.time > time).unwrap_or(self.keyframes.len());
+        self.keyframes.insert(index, Keyframe { time, value, interpolation });
+    }
+
+    pub fn get_keyframes(&self) -> &Vec<Keyframe<T>> {
+        &self.keyframes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+
+        if time <= first.time {
+            return Some(first.value.clone());
+        }
+
+        for pair in self.keyframes.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+
+            if time <= to.time {
+                let duration = to.time - from.time;
+                let amount = if duration > 0. { (time - from.time) / duration } else { 1. };
+
+                return Some(from.value.interpolate(&to.value, from.interpolation.apply(amount)));
+            }
+        }
+
+        self.keyframes.last().map(|keyframe| keyframe.value.clone())
+    }
+}
+
+impl <T: Interpolate + Clone> Default for Track<T> {
+    fn default() -> Self {
+        Track::new()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TransformTrack {
+    pub translation: Track<Vector3<f32>>,
+    pub rotation: Track<Quaternion<f32>>,
+    pub scale: Track<Vector3<f32>>,
+}
+
+impl TransformTrack {
+    pub fn new() -> Self {
+        TransformTrack {
+            translation: Track::new(),
+            rotation: Track::new(),
+            scale: Track::new()
+        }
+    }
+
+    pub fn get_matrix(&self, time: f32) -> Matrix4<f32> {
+        let translation = self.translation.sample(time).unwrap_or(Vector3::new(0., 0., 0.));
+        let rotation = self.rotation.sample(time).unwrap_or(Quaternion::identity());
+        let scale = self.scale.sample(time).unwrap_or(Vector3::new(1., 1., 1.));
+
+        Matrix4::compose(&translation, &rotation, &scale)
+    }
+
+    pub fn get_time_range(&self) -> (f32, f32) {
+        let times = self.translation.get_keyframes().iter().map(|keyframe| keyframe.time)
+            .chain(self.rotation.get_keyframes().iter().map(|keyframe| keyframe.time))
+            .chain(self.scale.get_keyframes().iter().map(|keyframe| keyframe.time));
+
+        times.fold(None, |range: Option<(f32, f32)>, time| match range {
+            Some((start, end)) => Some((f32::min(start, time), f32::max(end, time))),
+            None => Some((time, time))
+        }).unwrap_or((0., 0.))
+    }
+}
+
+impl Default for TransformTrack {
+    fn default() -> Self {
+        TransformTrack::new()
+    }
+}
@@ -3,6 +3,7 @@ use crate::renderables::triangle::Triangle;
 use crate::math::ray::Ray;
 use std::mem;
 
+#[derive(Clone, Copy, Debug)]
 pub struct BBox {
     pub min: Vector3<f32>,
     pub max: Vector3<f32>,
@@ -58,9 +59,94 @@ impl BBox {
         true
     }
 
+    // entry and exit distances of ray through the box, entry is negative when origin lies inside
+    pub fn ray_interval(&self, ray: &Ray) -> Option<(f32, f32)> {
+        let mut t_near = f32::NEG_INFINITY;
+        let mut t_far = f32::INFINITY;
+
+        for (origin, direction, min, max) in &[
+            (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+            (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+            (ray.origin.z, ray.direction.z, self.min.z, self.max.z)
+        ] {
+            if direction.abs() < f32::EPSILON {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let mut t0 = (min - origin) / direction;
+            let mut t1 = (max - origin) / direction;
+
+            if t0 > t1 {
+                mem::swap(&mut t0, &mut t1);
+            }
+
+            t_near = f32::max(t_near, t0);
+            t_far = f32::min(t_far, t1);
+        }
+
+        if t_near > t_far || t_far < 0. {
+            return None;
+        }
+
+        Some((t_near, t_far))
+    }
+
+    pub fn new_from_points(points: &[Vector3<f32>]) -> Self {
+        let mut bbox = BBox::new(
+            Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)
+        );
+
+        for point in points {
+            bbox.expand_by_point(point);
+        }
+
+        bbox
+    }
+
+    pub fn expand_by_point(&mut self, point: &Vector3<f32>) {
+        self.min.x = f32::min(self.min.x, point.x);
+        self.min.y = f32::min(self.min.y, point.y);
+        self.min.z = f32::min(self.min.z, point.z);
+
+        self.max.x = f32::max(self.max.x, point.x);
+        self.max.y = f32::max(self.max.y, point.y);
+        self.max.z = f32::max(self.max.z, point.z);
+    }
+
+    pub fn union(&self, other: &BBox) -> BBox {
+        let mut bbox = *self;
+        bbox.expand_by_point(&other.min);
+        bbox.expand_by_point(&other.max);
+
+        bbox
+    }
+
+    pub fn get_corners(&self) -> [Vector3<f32>; 8] {
+        let (min, max) = (&self.min, &self.max);
+
+        [
+            Vector3::new(min.x, min.y, min.z), Vector3::new(max.x, min.y, min.z),
+            Vector3::new(min.x, max.y, min.z), Vector3::new(max.x, max.y, min.z),
+            Vector3::new(min.x, min.y, max.z), Vector3::new(max.x, min.y, max.z),
+            Vector3::new(min.x, max.y, max.z), Vector3::new(max.x, max.y, max.z)
+        ]
+    }
+
+    pub fn get_center(&self) -> Vector3<f32> {
+        (&self.min + &self.max) * 0.5
+    }
+
+    pub fn get_size(&self) -> Vector3<f32> {
+        &self.max - &self.min
+    }
+
     pub fn new_from_triangles(triangles: &Vec<Triangle>) -> Self {
-        let mut min = Vector3::new(0.,0.,0.);
-        let mut max = Vector3::new(0.,0.,0.);
+        let mut min = Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
 
         for triangle in triangles {
             for vertex in &triangle.get_vertices() {
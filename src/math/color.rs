@@ -26,6 +26,10 @@ impl Color {
         self.g = g;
         self.b = b;
     }
+
+    pub fn get_luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
 }
 
 impl ops::Add<&f32> for Color {
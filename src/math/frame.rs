@@ -0,0 +1,54 @@
+use crate::math::vec3::Vector3;
+use crate::math::ray::Ray;
+
+// orthonormal local coordinate system, z axis is the main axis of a primitive
+#[derive(Clone, Copy, Debug)]
+pub struct Frame {
+    pub origin: Vector3<f32>,
+    pub x: Vector3<f32>,
+    pub y: Vector3<f32>,
+    pub z: Vector3<f32>,
+}
+
+impl Frame {
+    pub fn new(origin: Vector3<f32>, axis: Vector3<f32>) -> Self {
+        let mut z = axis;
+        z.normalize();
+
+        let mut x = if z.x.abs() > z.y.abs() {
+            Vector3::new(z.z, 0., -z.x)
+        } else {
+            Vector3::new(0., -z.z, z.y)
+        };
+        x.normalize();
+
+        let mut y = z;
+        y.cross(&x);
+
+        Frame { origin, x, y, z }
+    }
+
+    pub fn from_axes(origin: Vector3<f32>, x: Vector3<f32>, y: Vector3<f32>, z: Vector3<f32>) -> Self {
+        Frame { origin, x, y, z }
+    }
+
+    pub fn to_local_direction(&self, direction: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::new(direction.dot(&self.x), direction.dot(&self.y), direction.dot(&self.z))
+    }
+
+    pub fn to_local_point(&self, point: &Vector3<f32>) -> Vector3<f32> {
+        self.to_local_direction(&(point - &self.origin))
+    }
+
+    pub fn to_world_direction(&self, direction: &Vector3<f32>) -> Vector3<f32> {
+        &(&(self.x * direction.x) + &(self.y * direction.y)) + &(self.z * direction.z)
+    }
+
+    pub fn to_world_point(&self, point: &Vector3<f32>) -> Vector3<f32> {
+        &self.origin + &self.to_world_direction(point)
+    }
+
+    pub fn to_local_ray(&self, ray: &Ray) -> Ray {
+        Ray::new_with_time(self.to_local_point(&ray.origin), self.to_local_direction(&ray.direction), ray.time)
+    }
+}
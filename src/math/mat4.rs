@@ -1,6 +1,7 @@
 use std::ops::{DivAssign, AddAssign};
 use num::{Float};
 use crate::math::vec3::Vector3;
+use crate::math::quaternion::Quaternion;
 
 #[derive(Clone, Copy, Debug)]
 pub struct Matrix4 <T: Float> {
@@ -117,6 +118,43 @@ impl <T: Float> Matrix4 <T> where T: Float + DivAssign + AddAssign {
         me[ 13 ] = ( n13 * n24 * n31 - n14 * n23 * n31 + n14 * n21 * n33 - n11 * n24 * n33 - n13 * n21 * n34 + n11 * n23 * n34 ) * det_inv;
         me[ 14 ] = ( n14 * n22 * n31 - n12 * n24 * n31 - n14 * n21 * n32 + n11 * n24 * n32 + n12 * n21 * n34 - n11 * n22 * n34 ) * det_inv;
         me[ 15 ] = ( n12 * n23 * n31 - n13 * n22 * n31 + n13 * n21 * n32 - n11 * n23 * n32 - n12 * n21 * n33 + n11 * n22 * n33 ) * det_inv;
+
+        self.elements = me;
+    }
+
+    pub fn transpose(&mut self) {
+        let me = self.elements;
+
+        self.elements = [
+            me[ 0 ], me[ 4 ], me[ 8 ], me[ 12 ],
+            me[ 1 ], me[ 5 ], me[ 9 ], me[ 13 ],
+            me[ 2 ], me[ 6 ], me[ 10 ], me[ 14 ],
+            me[ 3 ], me[ 7 ], me[ 11 ], me[ 15 ]
+        ];
+    }
+
+    pub fn make_translation(&mut self, x: T, y: T, z: T) {
+        let zero = T::zero();
+        let one = T::one();
+
+        self.set([
+            one, zero, zero, zero,
+            zero, one, zero, zero,
+            zero, zero, one, zero,
+            x, y, z, one
+        ]);
+    }
+
+    pub fn make_scale(&mut self, x: T, y: T, z: T) {
+        let zero = T::zero();
+        let one = T::one();
+
+        self.set([
+            x, zero, zero, zero,
+            zero, y, zero, zero,
+            zero, zero, z, zero,
+            zero, zero, zero, one
+        ]);
     }
 
     pub fn rotate_y(&mut self, theta: T) {
@@ -186,6 +224,26 @@ impl <T: Float> Matrix4 <T> where T: Float + DivAssign + AddAssign {
         ])
     }
 
+    pub fn compose(position: &Vector3<T>, quaternion: &Quaternion<T>, scale: &Vector3<T>) -> Self {
+        let x = quaternion.x; let y = quaternion.y; let z = quaternion.z; let w = quaternion.w;
+        let x2 = x + x; let y2 = y + y; let z2 = z + z;
+        let xx = x * x2; let xy = x * y2; let xz = x * z2;
+        let yy = y * y2; let yz = y * z2; let zz = z * z2;
+        let wx = w * x2; let wy = w * y2; let wz = w * z2;
+
+        let sx = scale.x; let sy = scale.y; let sz = scale.z;
+
+        let zero = T::zero();
+        let one = T::one();
+
+        Matrix4::from_array([
+            ( one - ( yy + zz ) ) * sx, ( xy + wz ) * sx, ( xz - wy ) * sx, zero,
+            ( xy - wz ) * sy, ( one - ( xx + zz ) ) * sy, ( yz + wx ) * sy, zero,
+            ( xz + wy ) * sz, ( yz - wx ) * sz, ( one - ( xx + yy ) ) * sz, zero,
+            position.x, position.y, position.z, one
+        ])
+    }
+
     pub fn look_at(&mut self, position: &Vector3<T>, target: &Vector3<T>, up: &Vector3<T>)  {
         let mut z = position - target ;
 
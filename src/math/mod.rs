@@ -6,7 +6,40 @@ pub mod ray;
 pub mod bbox;
 pub mod color;
 pub mod spherical;
+pub mod quaternion;
+pub mod frame;
+pub mod animation;
 
 pub fn lerp <T: Float> (start: T, end: T, amt: T) -> T {
     return (T::one()-amt)*start+amt*end
 }
+
+// real roots of a*t^2 + b*t + c = 0 in ascending order, linear equation has single root so that
+// crossings of a surface keep alternating between entering and leaving it
+pub fn solve_quadratic <T: Float> (a: T, b: T, c: T) -> Vec<T> {
+    if a == T::zero() {
+        if b == T::zero() {
+            return Vec::new();
+        }
+
+        return vec![-c / b];
+    }
+
+    let discriminant = b * b - T::from(4.).unwrap() * a * c;
+
+    if discriminant < T::zero() {
+        return Vec::new();
+    }
+
+    let root = discriminant.sqrt();
+    let q = if b < T::zero() {
+        -(b - root) / T::from(2.).unwrap()
+    } else {
+        -(b + root) / T::from(2.).unwrap()
+    };
+
+    let t0 = q / a;
+    let t1 = if q == T::zero() { t0 } else { c / q };
+
+    if t0 < t1 { vec![t0, t1] } else { vec![t1, t0] }
+}
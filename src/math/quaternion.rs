@@ -0,0 +1,114 @@
+use std::fmt;
+use std::ops::DivAssign;
+use num::{Float};
+use crate::math::vec3::Vector3;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Quaternion <T: Float> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T
+}
+
+impl <T: Float> Quaternion <T> where T: Float + DivAssign {
+    pub fn new(x: T, y: T, z: T, w: T) -> Self {
+        Quaternion {x, y, z, w}
+    }
+
+    pub fn identity() -> Self {
+        Quaternion::new(T::zero(), T::zero(), T::zero(), T::one())
+    }
+
+    pub fn from_axis_angle(axis: &Vector3<T>, angle: T) -> Self {
+        let half_angle = angle / T::from(2.).unwrap();
+        let s = Float::sin(half_angle);
+
+        Quaternion {
+            x: axis.x * s,
+            y: axis.y * s,
+            z: axis.z * s,
+            w: Float::cos(half_angle)
+        }
+    }
+
+    pub fn length(&self) -> T {
+        Float::sqrt(self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w)
+    }
+
+    pub fn normalize(&mut self) -> &mut Self {
+        let length = self.length();
+
+        if length == T::zero() {
+            *self = Quaternion::identity();
+        } else {
+            self.x /= length;
+            self.y /= length;
+            self.z /= length;
+            self.w /= length;
+        }
+
+        self
+    }
+
+    pub fn multiply(&mut self, other: &Quaternion<T>) -> &mut Self {
+        let qax = self.x; let qay = self.y; let qaz = self.z; let qaw = self.w;
+        let qbx = other.x; let qby = other.y; let qbz = other.z; let qbw = other.w;
+
+        self.x = qax * qbw + qaw * qbx + qay * qbz - qaz * qby;
+        self.y = qay * qbw + qaw * qby + qaz * qbx - qax * qbz;
+        self.z = qaz * qbw + qaw * qbz + qax * qby - qay * qbx;
+        self.w = qaw * qbw - qax * qbx - qay * qby - qaz * qbz;
+
+        self
+    }
+
+    pub fn dot(&self, other: &Quaternion<T>) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    // spherical interpolation along shortest arc
+    pub fn slerp(&self, other: &Quaternion<T>, t: T) -> Self {
+        let mut cos_half_theta = self.dot(other);
+        let mut end = *other;
+
+        if cos_half_theta < T::zero() {
+            end = Quaternion::new(-other.x, -other.y, -other.z, -other.w);
+            cos_half_theta = -cos_half_theta;
+        }
+
+        let (start_amount, end_amount) = if cos_half_theta > T::from(0.9995).unwrap() {
+            (T::one() - t, t)
+        } else {
+            let half_theta = Float::acos(cos_half_theta);
+            let sin_half_theta = Float::sin(half_theta);
+
+            (
+                Float::sin((T::one() - t) * half_theta) / sin_half_theta,
+                Float::sin(t * half_theta) / sin_half_theta
+            )
+        };
+
+        let mut result = Quaternion::new(
+            self.x * start_amount + end.x * end_amount,
+            self.y * start_amount + end.y * end_amount,
+            self.z * start_amount + end.z * end_amount,
+            self.w * start_amount + end.w * end_amount
+        );
+        result.normalize();
+
+        result
+    }
+}
+
+impl <T: Float> PartialEq for Quaternion<T> {
+    fn eq(&self, other: &Quaternion<T>) -> bool {
+        self.x == other.x && self.y == other.y && self.z == other.z && self.w == other.w
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Quaternion<T> where T: Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "x: {}, y: {}, z: {}, w: {}", self.x, self.y, self.z, self.w)
+    }
+}
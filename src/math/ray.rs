@@ -2,11 +2,16 @@ use crate::math::vec3::Vector3;
 
 pub struct Ray {
     pub origin: Vector3<f32>,
-    pub direction: Vector3<f32>
+    pub direction: Vector3<f32>,
+    pub time: f32
 }
 
 impl Ray {
     pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
-        Ray {origin, direction}
+        Ray {origin, direction, time: 0.}
+    }
+
+    pub fn new_with_time(origin: Vector3<f32>, direction: Vector3<f32>, time: f32) -> Self {
+        Ray {origin, direction, time}
     }
 }
@@ -77,6 +77,20 @@ impl <T: Float> Vector3 <T> where T: Float + DivAssign + Copy {
         self.y = ( e[ 1 ] * x + e[ 5 ] * y + e[ 9 ] * z + e[ 13 ] ) * w;
         self.z = ( e[ 2 ] * x + e[ 6 ] * y + e[ 10 ] * z + e[ 14 ] ) * w;
     }
+
+    pub fn transform_direction(&mut self, m: &Matrix4<T> ) -> &mut Self {
+        let x = self.x;
+        let y = self.y;
+        let z = self.z;
+
+        let e = &m.elements;
+
+        self.x = e[ 0 ] * x + e[ 4 ] * y + e[ 8 ] * z;
+        self.y = e[ 1 ] * x + e[ 5 ] * y + e[ 9 ] * z;
+        self.z = e[ 2 ] * x + e[ 6 ] * y + e[ 10 ] * z;
+
+        self.normalize()
+    }
 }
 
 impl <T: Float> ops::Add<&Vector3<T>> for &Vector3<T> {
@@ -0,0 +1,112 @@
+use crate::renderables::renderable::{Renderable, IntersectionData, sort_intersections};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+use crate::math::frame::Frame;
+use crate::math::solve_quadratic;
+use std::f32::consts::PI;
+
+// all points within radius of segment between start and end
+pub struct Capsule {
+    material: Material,
+    frame: Frame,
+    radius: f32,
+    length: f32,
+}
+
+impl Capsule {
+    pub fn new(start: Vector3<f32>, end: Vector3<f32>, radius: f32, material: Material) -> Self {
+        let axis = &end - &start;
+
+        Capsule {
+            material,
+            frame: Frame::new(start, axis),
+            radius,
+            length: axis.magnitude()
+        }
+    }
+
+    fn create_intersection(&self, hit: &Vector3<f32>, distance: f32) -> IntersectionData {
+        let closest_on_segment = Vector3::new(0., 0., f32::min(f32::max(hit.z, 0.), self.length));
+        let mut normal = hit - &closest_on_segment;
+        normal.normalize();
+
+        // v runs over the whole surface from start cap pole to end cap pole
+        let arc = PI * self.radius * 0.5;
+        let along = if hit.z < 0. {
+            arc - f32::acos(f32::min(-normal.z, 1.)) * self.radius
+        } else if hit.z > self.length {
+            arc + self.length + f32::acos(f32::min(normal.z, 1.)) * self.radius
+        } else {
+            arc + hit.z
+        };
+
+        IntersectionData {
+            distance,
+            normal: self.frame.to_world_direction(&normal),
+            uv: (f32::atan2(hit.y, hit.x) / (2. * PI) + 0.5, along / (2. * arc + self.length)),
+            vertex_color: None
+        }
+    }
+}
+
+impl Renderable for Capsule {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        self.get_intersections(ray).into_iter().next()
+    }
+
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        let local_ray = self.frame.to_local_ray(ray);
+        let (o, d) = (&local_ray.origin, &local_ray.direction);
+        let radius2 = self.radius * self.radius;
+        let mut intersections = Vec::new();
+
+        let a = d.x * d.x + d.y * d.y;
+        if a > f32::EPSILON {
+            for t in &solve_quadratic(a, 2. * (o.x * d.x + o.y * d.y), o.x * o.x + o.y * o.y - radius2) {
+                let hit = o + &(*d * *t);
+
+                if hit.z >= 0. && hit.z <= self.length {
+                    intersections.push(self.create_intersection(&hit, *t));
+                }
+            }
+        }
+
+        // hemispherical caps only count outside of segment range
+        for (center_z, is_start) in &[(0., true), (self.length, false)] {
+            let oc = Vector3::new(o.x, o.y, o.z - center_z);
+
+            for t in &solve_quadratic(d.dot(d), 2. * oc.dot(d), oc.dot(&oc) - radius2) {
+                let hit = o + &(*d * *t);
+
+                if (*is_start && hit.z < 0.) || (!*is_start && hit.z > self.length) {
+                    intersections.push(self.create_intersection(&hit, *t));
+                }
+            }
+        }
+
+        sort_intersections(&mut intersections);
+        intersections
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let end = self.frame.to_world_point(&Vector3::new(0., 0., self.length));
+        let radius = Vector3::new(self.radius, self.radius, self.radius);
+
+        Some(BBox::new_from_points(&[
+            &self.frame.origin - &radius,
+            &self.frame.origin + &radius,
+            &end - &radius,
+            &end + &radius
+        ]))
+    }
+}
@@ -0,0 +1,109 @@
+use crate::renderables::renderable::{Renderable, IntersectionData, sort_intersections};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+use crate::math::frame::Frame;
+use crate::math::solve_quadratic;
+use std::f32::consts::PI;
+
+// cone with capped circular base, tapering to apex
+pub struct Cone {
+    material: Material,
+    frame: Frame,
+    radius: f32,
+    height: f32,
+}
+
+impl Cone {
+    pub fn new(base: Vector3<f32>, apex: Vector3<f32>, radius: f32, material: Material) -> Self {
+        let axis = &apex - &base;
+
+        Cone {
+            material,
+            frame: Frame::new(base, axis),
+            radius,
+            height: axis.magnitude()
+        }
+    }
+}
+
+impl Renderable for Cone {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        self.get_intersections(ray).into_iter().next()
+    }
+
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        let local_ray = self.frame.to_local_ray(ray);
+        let (o, d) = (&local_ray.origin, &local_ray.direction);
+        let mut intersections = Vec::new();
+
+        // x^2 + y^2 = k^2 * (h - z)^2
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let h = self.height - o.z;
+
+        let a = d.x * d.x + d.y * d.y - k2 * d.z * d.z;
+        let b = 2. * (o.x * d.x + o.y * d.y + k2 * h * d.z);
+        let c = o.x * o.x + o.y * o.y - k2 * h * h;
+
+        for t in &solve_quadratic(a, b, c) {
+            let hit = o + &(*d * *t);
+
+            if hit.z >= 0. && hit.z <= self.height {
+                let mut normal = Vector3::new(hit.x, hit.y, k2 * (self.height - hit.z));
+                normal.normalize();
+
+                intersections.push(IntersectionData {
+                    distance: *t,
+                    normal: self.frame.to_world_direction(&normal),
+                    uv: (f32::atan2(hit.y, hit.x) / (2. * PI) + 0.5, hit.z / self.height),
+                    vertex_color: None
+                });
+            }
+        }
+
+        if d.z.abs() > f32::EPSILON {
+            let t = -o.z / d.z;
+            let hit = o + &(*d * t);
+            let distance_to_axis = f32::sqrt(hit.x * hit.x + hit.y * hit.y);
+
+            if distance_to_axis <= self.radius {
+                intersections.push(IntersectionData {
+                    distance: t,
+                    normal: self.frame.z * -1.,
+                    uv: (f32::atan2(hit.y, hit.x) / (2. * PI) + 0.5, distance_to_axis / self.radius),
+                    vertex_color: None
+                });
+            }
+        }
+
+        sort_intersections(&mut intersections);
+        intersections
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let axis = &self.frame.z;
+        let apex = self.frame.to_world_point(&Vector3::new(0., 0., self.height));
+
+        let extent = Vector3::new(
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.x * axis.x)),
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.y * axis.y)),
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.z * axis.z))
+        );
+
+        Some(BBox::new_from_points(&[
+            &self.frame.origin - &extent,
+            &self.frame.origin + &extent,
+            apex
+        ]))
+    }
+}
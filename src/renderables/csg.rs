@@ -0,0 +1,128 @@
+use crate::renderables::renderable::{Renderable, IntersectionData};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::bbox::BBox;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    fn is_inside(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right
+        }
+    }
+}
+
+// operands are expected to be closed surfaces with outward facing normals
+pub struct Csg {
+    operation: CsgOperation,
+    left: Arc<dyn Renderable + Send>,
+    right: Arc<dyn Renderable + Send>,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Arc<dyn Renderable + Send>, right: Arc<dyn Renderable + Send>) -> Self {
+        Csg {
+            operation,
+            left,
+            right
+        }
+    }
+
+    pub fn union(left: Arc<dyn Renderable + Send>, right: Arc<dyn Renderable + Send>) -> Self {
+        Csg::new(CsgOperation::Union, left, right)
+    }
+
+    pub fn intersection(left: Arc<dyn Renderable + Send>, right: Arc<dyn Renderable + Send>) -> Self {
+        Csg::new(CsgOperation::Intersection, left, right)
+    }
+
+    pub fn difference(left: Arc<dyn Renderable + Send>, right: Arc<dyn Renderable + Send>) -> Self {
+        Csg::new(CsgOperation::Difference, left, right)
+    }
+
+    pub fn get_operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    // ray starts inside a closed surface when its nearest crossing is an exit
+    fn starts_inside(ray: &Ray, intersections: &[IntersectionData]) -> bool {
+        intersections.first().is_some_and(|intersection| intersection.normal.dot(&ray.direction) > 0.)
+    }
+}
+
+impl Renderable for Csg {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        self.get_intersections(ray).into_iter().next()
+    }
+
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        let left = self.left.get_intersections(ray);
+        let right = self.right.get_intersections(ray);
+
+        let mut inside_left = Csg::starts_inside(ray, &left);
+        let mut inside_right = Csg::starts_inside(ray, &right);
+        let mut inside = self.operation.is_inside(inside_left, inside_right);
+
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+        let mut intersections = Vec::new();
+
+        loop {
+            let from_left = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => l.distance <= r.distance,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break
+            };
+
+            let mut intersection = if from_left {
+                inside_left = !inside_left;
+                left.next().unwrap()
+            } else {
+                inside_right = !inside_right;
+                right.next().unwrap()
+            };
+
+            let inside_after = self.operation.is_inside(inside_left, inside_right);
+
+            // only crossings which change the combined solid's state lie on its surface
+            if inside_after != inside {
+                if !from_left && self.operation == CsgOperation::Difference {
+                    intersection.normal = intersection.normal * -1.;
+                }
+
+                intersections.push(intersection);
+                inside = inside_after;
+            }
+        }
+
+        intersections
+    }
+
+    fn get_material(&self) -> &Material {
+        self.left.get_material()
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Arc::get_mut(&mut self.left).and_then(|renderable| renderable.get_material_mut())
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        match self.operation {
+            CsgOperation::Union => match (self.left.get_bbox(), self.right.get_bbox()) {
+                (Some(left), Some(right)) => Some(left.union(&right)),
+                _ => None
+            },
+            _ => self.left.get_bbox()
+        }
+    }
+}
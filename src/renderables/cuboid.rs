@@ -0,0 +1,182 @@
+use crate::renderables::renderable::{Renderable, IntersectionData, sort_intersections};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+use crate::math::frame::Frame;
+use crate::math::mat4::Matrix4;
+use crate::math::quaternion::Quaternion;
+
+pub struct Cuboid {
+    material: Material,
+    frame: Frame,
+    half_size: Vector3<f32>,
+}
+
+impl Cuboid {
+    pub fn new(center: Vector3<f32>, size: Vector3<f32>, material: Material) -> Self {
+        Cuboid::oriented(center, size, &Quaternion::identity(), material)
+    }
+
+    pub fn oriented(center: Vector3<f32>, size: Vector3<f32>, rotation: &Quaternion<f32>, material: Material) -> Self {
+        let rotation_matrix = Matrix4::compose(&Vector3::new(0., 0., 0.), rotation, &Vector3::new(1., 1., 1.));
+
+        let mut x = Vector3::new(1., 0., 0.);
+        let mut y = Vector3::new(0., 1., 0.);
+        let mut z = Vector3::new(0., 0., 1.);
+        x.transform_direction(&rotation_matrix);
+        y.transform_direction(&rotation_matrix);
+        z.transform_direction(&rotation_matrix);
+
+        Cuboid {
+            material,
+            frame: Frame::from_axes(center, x, y, z),
+            half_size: size * 0.5,
+        }
+    }
+
+    fn get_axis_value(vector: &Vector3<f32>, axis: usize) -> f32 {
+        match axis {
+            0 => vector.x,
+            1 => vector.y,
+            _ => vector.z
+        }
+    }
+
+    fn create_intersection(&self, local_ray: &Ray, distance: f32, axis: usize, sign: f32) -> IntersectionData {
+        let hit = &local_ray.origin + &(local_ray.direction * distance);
+
+        let mut local_normal = Vector3::new(0., 0., 0.);
+        match axis {
+            0 => local_normal.x = sign,
+            1 => local_normal.y = sign,
+            _ => local_normal.z = sign
+        }
+
+        // face uv uses the two remaining axes
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let u = Cuboid::get_axis_value(&hit, a) / (2. * Cuboid::get_axis_value(&self.half_size, a)) + 0.5;
+        let v = Cuboid::get_axis_value(&hit, b) / (2. * Cuboid::get_axis_value(&self.half_size, b)) + 0.5;
+
+        IntersectionData {
+            distance,
+            normal: self.frame.to_world_direction(&local_normal),
+            uv: (u, v),
+            vertex_color: None
+        }
+    }
+}
+
+impl Renderable for Cuboid {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        self.get_intersections(ray).into_iter().next()
+    }
+
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        let local_ray = self.frame.to_local_ray(ray);
+
+        let mut t_near = f32::NEG_INFINITY;
+        let mut t_far = f32::INFINITY;
+        let mut near_face = (0, -1.);
+        let mut far_face = (0, 1.);
+
+        for axis in 0..3 {
+            let origin = Cuboid::get_axis_value(&local_ray.origin, axis);
+            let direction = Cuboid::get_axis_value(&local_ray.direction, axis);
+            let half_size = Cuboid::get_axis_value(&self.half_size, axis);
+
+            if direction.abs() < f32::EPSILON {
+                if origin.abs() > half_size {
+                    return Vec::new();
+                }
+                continue;
+            }
+
+            let mut t0 = (-half_size - origin) / direction;
+            let mut t1 = (half_size - origin) / direction;
+            let mut signs = (-1., 1.);
+
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+                signs = (1., -1.);
+            }
+
+            if t0 > t_near {
+                t_near = t0;
+                near_face = (axis, signs.0);
+            }
+
+            if t1 < t_far {
+                t_far = t1;
+                far_face = (axis, signs.1);
+            }
+
+            if t_near > t_far {
+                return Vec::new();
+            }
+        }
+
+        let mut intersections = vec![
+            self.create_intersection(&local_ray, t_near, near_face.0, near_face.1),
+            self.create_intersection(&local_ray, t_far, far_face.0, far_face.1)
+        ];
+        sort_intersections(&mut intersections);
+
+        intersections
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    // face is picked proportionally to its area, r1 is rescaled to be reused inside of it
+    fn sample_surface(&self, r1: f32, r2: f32, _time: f32) -> Option<(Vector3<f32>, Vector3<f32>, f32)> {
+        let half_size = [self.half_size.x, self.half_size.y, self.half_size.z];
+        let face_areas = [4. * half_size[1] * half_size[2], 4. * half_size[0] * half_size[2], 4. * half_size[0] * half_size[1]];
+        let area = 2. * (face_areas[0] + face_areas[1] + face_areas[2]);
+
+        if area <= 0. {
+            return None;
+        }
+
+        let mut target = r1 * area;
+
+        for face in 0..6 {
+            let axis = face / 2;
+            let face_area = face_areas[axis];
+
+            if target < face_area || face == 5 {
+                let r1 = (target / face_area).clamp(0., 1.);
+                let sign = if face % 2 == 0 { -1. } else { 1. };
+                let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+
+                let mut local_point = [0.; 3];
+                let mut local_normal = [0.; 3];
+                local_point[axis] = sign * half_size[axis];
+                local_point[a] = (2. * r1 - 1.) * half_size[a];
+                local_point[b] = (2. * r2 - 1.) * half_size[b];
+                local_normal[axis] = sign;
+
+                let point = self.frame.to_world_point(&Vector3::new(local_point[0], local_point[1], local_point[2]));
+                let normal = self.frame.to_world_direction(&Vector3::new(local_normal[0], local_normal[1], local_normal[2]));
+
+                return Some((point, normal, area));
+            }
+
+            target -= face_area;
+        }
+
+        None
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let local = BBox::new(-self.half_size, self.half_size);
+        let corners: Vec<Vector3<f32>> = local.get_corners().iter().map(|corner| self.frame.to_world_point(corner)).collect();
+
+        Some(BBox::new_from_points(&corners))
+    }
+}
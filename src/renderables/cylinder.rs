@@ -0,0 +1,106 @@
+use crate::renderables::renderable::{Renderable, IntersectionData, sort_intersections};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+use crate::math::frame::Frame;
+use crate::math::solve_quadratic;
+use std::f32::consts::PI;
+
+// capped cylinder between centers of its bottom and top caps
+pub struct Cylinder {
+    material: Material,
+    frame: Frame,
+    radius: f32,
+    height: f32,
+}
+
+impl Cylinder {
+    pub fn new(bottom: Vector3<f32>, top: Vector3<f32>, radius: f32, material: Material) -> Self {
+        let axis = &top - &bottom;
+
+        Cylinder {
+            material,
+            frame: Frame::new(bottom, axis),
+            radius,
+            height: axis.magnitude()
+        }
+    }
+}
+
+impl Renderable for Cylinder {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        self.get_intersections(ray).into_iter().next()
+    }
+
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        let local_ray = self.frame.to_local_ray(ray);
+        let (o, d) = (&local_ray.origin, &local_ray.direction);
+        let mut intersections = Vec::new();
+
+        let a = d.x * d.x + d.y * d.y;
+        let b = 2. * (o.x * d.x + o.y * d.y);
+        let c = o.x * o.x + o.y * o.y - self.radius * self.radius;
+
+        if a > f32::EPSILON {
+            for t in &solve_quadratic(a, b, c) {
+                let hit = o + &(*d * *t);
+
+                if hit.z >= 0. && hit.z <= self.height {
+                    intersections.push(IntersectionData {
+                        distance: *t,
+                        normal: self.frame.to_world_direction(&Vector3::new(hit.x / self.radius, hit.y / self.radius, 0.)),
+                        uv: (f32::atan2(hit.y, hit.x) / (2. * PI) + 0.5, hit.z / self.height),
+                        vertex_color: None
+                    });
+                }
+            }
+        }
+
+        if d.z.abs() > f32::EPSILON {
+            for (cap_height, sign) in &[(0., -1.), (self.height, 1.)] {
+                let t = (cap_height - o.z) / d.z;
+                let hit = o + &(*d * t);
+                let distance_to_axis = f32::sqrt(hit.x * hit.x + hit.y * hit.y);
+
+                if distance_to_axis <= self.radius {
+                    intersections.push(IntersectionData {
+                        distance: t,
+                        normal: self.frame.z * *sign,
+                        uv: (f32::atan2(hit.y, hit.x) / (2. * PI) + 0.5, distance_to_axis / self.radius),
+                        vertex_color: None
+                    });
+                }
+            }
+        }
+
+        sort_intersections(&mut intersections);
+        intersections
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let axis = &self.frame.z;
+        let top = self.frame.to_world_point(&Vector3::new(0., 0., self.height));
+
+        let extent = Vector3::new(
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.x * axis.x)),
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.y * axis.y)),
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.z * axis.z))
+        );
+
+        Some(BBox::new_from_points(&[
+            &self.frame.origin - &extent,
+            &self.frame.origin + &extent,
+            &top - &extent,
+            &top + &extent
+        ]))
+    }
+}
@@ -0,0 +1,88 @@
+use crate::renderables::renderable::{Renderable, IntersectionData};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+use crate::math::frame::Frame;
+use std::f32::consts::PI;
+
+const EPSILON: f32 = 0.00001;
+
+pub struct Disk {
+    material: Material,
+    frame: Frame,
+    radius: f32,
+}
+
+impl Disk {
+    pub fn new(center: Vector3<f32>, normal: Vector3<f32>, radius: f32, material: Material) -> Self {
+        Disk {
+            material,
+            frame: Frame::new(center, normal),
+            radius
+        }
+    }
+
+    pub fn get_normal(&self) -> Vector3<f32> {
+        self.frame.z
+    }
+}
+
+impl Renderable for Disk {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        let local_ray = self.frame.to_local_ray(ray);
+
+        if local_ray.direction.z.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let t = -local_ray.origin.z / local_ray.direction.z;
+
+        if t < EPSILON {
+            return None;
+        }
+
+        let hit = &local_ray.origin + &(local_ray.direction * t);
+        let distance_to_center = f32::sqrt(hit.x * hit.x + hit.y * hit.y);
+
+        if distance_to_center > self.radius {
+            return None;
+        }
+
+        Some(IntersectionData {
+            distance: t,
+            normal: self.frame.z,
+            uv: (f32::atan2(hit.y, hit.x) / (2. * PI) + 0.5, distance_to_center / self.radius),
+            vertex_color: None
+        })
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    fn sample_surface(&self, r1: f32, r2: f32, _time: f32) -> Option<(Vector3<f32>, Vector3<f32>, f32)> {
+        let radius = self.radius * f32::sqrt(r1);
+        let phi = 2. * PI * r2;
+        let point = self.frame.to_world_point(&Vector3::new(radius * f32::cos(phi), radius * f32::sin(phi), 0.));
+
+        Some((point, self.frame.z, PI * self.radius * self.radius))
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let axis = &self.frame.z;
+
+        // extent of a circle along each world axis
+        let extent = Vector3::new(
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.x * axis.x)),
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.y * axis.y)),
+            self.radius * f32::sqrt(f32::max(0., 1. - axis.z * axis.z))
+        );
+
+        Some(BBox::new(&self.frame.origin - &extent, &self.frame.origin + &extent))
+    }
+}
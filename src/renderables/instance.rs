@@ -0,0 +1,199 @@
+use crate::renderables::renderable::{Renderable, IntersectionData};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::mat4::Matrix4;
+use crate::math::bbox::BBox;
+use crate::math::animation::TransformTrack;
+use crate::math::lerp;
+use crate::math::frame::Frame;
+use std::sync::Arc;
+
+const MOTION_BBOX_SAMPLES: usize = 16;
+
+struct InstanceTransform {
+    transform: Matrix4<f32>,
+    inverse_transform: Matrix4<f32>,
+    normal_matrix: Matrix4<f32>,
+}
+
+impl InstanceTransform {
+    fn new(transform: Matrix4<f32>) -> Self {
+        let mut inverse_transform = transform;
+        inverse_transform.inverse();
+
+        let mut normal_matrix = inverse_transform;
+        normal_matrix.transpose();
+
+        InstanceTransform {
+            transform,
+            inverse_transform,
+            normal_matrix
+        }
+    }
+
+    fn to_local_ray(&self, ray: &Ray) -> Ray {
+        let mut origin = ray.origin;
+        origin.apply_matrix(&self.inverse_transform);
+
+        let mut direction = ray.direction;
+        direction.transform_direction(&self.inverse_transform);
+
+        Ray::new_with_time(origin, direction, ray.time)
+    }
+
+    fn to_world_intersection(&self, ray: &Ray, local_ray: &Ray, local_intersection: IntersectionData) -> IntersectionData {
+        let mut hit_point = &local_ray.origin + &(local_ray.direction * local_intersection.distance);
+        hit_point.apply_matrix(&self.transform);
+
+        let mut normal = local_intersection.normal;
+        normal.transform_direction(&self.normal_matrix);
+
+        IntersectionData {
+            distance: (&hit_point - &ray.origin).magnitude(),
+            normal,
+            uv: local_intersection.uv,
+            vertex_color: local_intersection.vertex_color
+        }
+    }
+
+    fn transform_bbox(&self, bbox: &BBox) -> BBox {
+        let mut corners = bbox.get_corners();
+
+        for corner in corners.iter_mut() {
+            corner.apply_matrix(&self.transform);
+        }
+
+        BBox::new_from_points(&corners)
+    }
+}
+
+pub struct Instance {
+    renderable: Arc<dyn Renderable + Send>,
+    transform: InstanceTransform,
+    motion: Option<TransformTrack>,
+    // replaces material of shared renderable once it is edited through this instance
+    material: Option<Material>,
+}
+
+impl Instance {
+    pub fn new(renderable: Arc<dyn Renderable + Send>, transform: Matrix4<f32>) -> Self {
+        Instance {
+            renderable,
+            transform: InstanceTransform::new(transform),
+            motion: None,
+            material: None
+        }
+    }
+
+    // transform is evaluated from track at time of each ray
+    pub fn animated(renderable: Arc<dyn Renderable + Send>, motion: TransformTrack) -> Self {
+        Instance {
+            renderable,
+            transform: InstanceTransform::new(motion.get_matrix(0.)),
+            motion: Some(motion),
+            material: None
+        }
+    }
+
+    pub fn get_transform(&self) -> &Matrix4<f32> {
+        &self.transform.transform
+    }
+
+    pub fn get_motion(&self) -> Option<&TransformTrack> {
+        self.motion.as_ref()
+    }
+
+    pub fn get_renderable(&self) -> &Arc<dyn Renderable + Send> {
+        &self.renderable
+    }
+
+    fn with_transform<R, F>(&self, time: f32, callback: F) -> R where F: FnOnce(&InstanceTransform) -> R {
+        match &self.motion {
+            Some(motion) => callback(&InstanceTransform::new(motion.get_matrix(time))),
+            None => callback(&self.transform)
+        }
+    }
+}
+
+impl Renderable for Instance {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        self.with_transform(ray.time, |transform| {
+            let local_ray = transform.to_local_ray(ray);
+
+            self.renderable.intersects(&local_ray)
+                .map(|local_intersection| transform.to_world_intersection(ray, &local_ray, local_intersection))
+        })
+    }
+
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        self.with_transform(ray.time, |transform| {
+            let local_ray = transform.to_local_ray(ray);
+
+            self.renderable.get_intersections(&local_ray).into_iter()
+                .map(|local_intersection| transform.to_world_intersection(ray, &local_ray, local_intersection))
+                .collect()
+        })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.material.as_ref().unwrap_or_else(|| self.renderable.get_material())
+    }
+
+    // renderable can be shared with other instances and nodes, so its material is copied on first edit
+    // and changes affect only this instance
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        if self.material.is_none() {
+            self.material = Some(self.renderable.get_material().clone());
+        }
+
+        self.material.as_mut()
+    }
+
+    // area is scaled by how transform stretches surface around sampled point, so it stays inverse
+    // density of the point even for non uniform scale
+    fn sample_surface(&self, r1: f32, r2: f32, time: f32) -> Option<(Vector3<f32>, Vector3<f32>, f32)> {
+        let (local_point, local_normal, local_area) = self.renderable.sample_surface(r1, r2, time)?;
+
+        self.with_transform(time, |transform| {
+            let mut point = local_point;
+            point.apply_matrix(&transform.transform);
+
+            let mut normal = local_normal;
+            normal.transform_direction(&transform.normal_matrix);
+            normal.normalize();
+
+            // unit square on tangent plane mapped to world
+            let tangent_frame = Frame::new(local_point, local_normal);
+            let mut tangent_x = &local_point + &tangent_frame.x;
+            let mut tangent_y = &local_point + &tangent_frame.y;
+            tangent_x.apply_matrix(&transform.transform);
+            tangent_y.apply_matrix(&transform.transform);
+
+            let mut stretch = &tangent_x - &point;
+            stretch.cross(&(&tangent_y - &point));
+
+            Some((point, normal, local_area * stretch.magnitude()))
+        })
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let bbox = self.renderable.get_bbox()?;
+
+        match &self.motion {
+            Some(motion) => {
+                let (start, end) = motion.get_time_range();
+                let mut result = self.transform.transform_bbox(&bbox);
+
+                // rotation sweeps between keyframes, so bounds are sampled densely over the range
+                for i in 0..=MOTION_BBOX_SAMPLES {
+                    let time = lerp(start, end, i as f32 / MOTION_BBOX_SAMPLES as f32);
+                    result = result.union(&InstanceTransform::new(motion.get_matrix(time)).transform_bbox(&bbox));
+                }
+
+                Some(result)
+            }
+            None => Some(self.transform.transform_bbox(&bbox))
+        }
+    }
+}
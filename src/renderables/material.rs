@@ -1,20 +1,106 @@
 use crate::math::color::Color;
+use crate::renderables::texture::Texture;
+use crate::renderer::medium::Medium;
+use std::sync::Arc;
 
-#[derive(Clone, Copy, Debug)]
+// random walk subsurface scattering inside closed surface
+#[derive(Clone, Debug)]
+pub struct Subsurface {
+    pub albedo: Color,
+    pub mean_free_path: [f32; 3],
+    pub anisotropy: f32,
+}
+
+impl Subsurface {
+    pub fn new(albedo: Color, mean_free_path: [f32; 3]) -> Self {
+        Subsurface {
+            albedo,
+            mean_free_path,
+            anisotropy: 0.
+        }
+    }
+
+    // single scattering albedo giving requested multiple scattering surface albedo (Christensen, Burley 2015)
+    pub fn get_single_scattering_albedo(&self, channel: usize) -> f32 {
+        let albedo = match channel {
+            0 => self.albedo.r,
+            1 => self.albedo.g,
+            _ => self.albedo.b
+        } / 255.;
+        let albedo = albedo.clamp(0., 0.999);
+
+        let root = 4.09712 + 4.20863 * albedo - f32::sqrt(9.59217 + 41.6808 * albedo + 17.7126 * albedo * albedo);
+        1. - root * root
+    }
+
+    pub fn get_extinction(&self, channel: usize) -> f32 {
+        1. / f32::max(self.mean_free_path[channel], f32::EPSILON)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Material {
     pub diffuse_color: Color,
+    pub ambient_color: Color,
+    pub specular_color: Color,
+    pub emissive_color: Color,
+    pub transmission_color: Color,
     pub shininess: f32,
     pub opacity: f32,
-    pub reflectivity: f32
+    pub reflectivity: f32,
+    pub refractive_index: f32,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub sheen: f32,
+    pub anisotropy: f32,
+    pub anisotropy_rotation: f32,
+    pub illumination_model: u8,
+    pub diffuse_texture: Option<Arc<Texture>>,
+    // interior medium, surface then only separates it from scene medium
+    pub medium: Option<Arc<Medium>>,
+    pub subsurface: Option<Subsurface>
 }
 
 impl Material {
     pub fn new() -> Self {
         Material {
             diffuse_color: Color::new(255.,255.,255.),
+            ambient_color: Color::new(0.,0.,0.),
+            specular_color: Color::new(255.,255.,255.),
+            emissive_color: Color::new(0.,0.,0.),
+            transmission_color: Color::new(255.,255.,255.),
             opacity: 1.,
             shininess: 1.,
-            reflectivity: 0.2
+            reflectivity: 0.2,
+            refractive_index: 1.,
+            metallic: 0.,
+            roughness: 0.,
+            sheen: 0.,
+            anisotropy: 0.,
+            anisotropy_rotation: 0.,
+            illumination_model: 2,
+            diffuse_texture: None,
+            medium: None,
+            subsurface: None
+        }
+    }
+
+    // transmission filter normalized so that it only tints refracted light
+    pub fn get_transmission_tint(&self) -> Color {
+        let color = self.transmission_color;
+        let max_channel = f32::max(color.r, f32::max(color.g, color.b));
+
+        if max_channel > 0. {
+            color / max_channel
+        } else {
+            Color::new(1., 1., 1.)
+        }
+    }
+
+    pub fn get_diffuse_color(&self, uv: (f32, f32)) -> Color {
+        match &self.diffuse_texture {
+            Some(texture) => self.diffuse_color * (texture.sample(uv.0, uv.1) / 255.),
+            None => self.diffuse_color
         }
     }
 }
@@ -1,27 +1,293 @@
-use crate::renderables::renderable::{Renderable, IntersectionData};
+use crate::renderables::renderable::{Renderable, IntersectionData, sort_intersections};
 use crate::math::ray::Ray;
 use crate::renderables::material::Material;
 use crate::renderables::triangle::Triangle;
 use crate::math::bbox::BBox;
+use crate::math::vec3::Vector3;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct MeshOptions {
+    pub weld_distance: Option<f32>,
+    pub remove_degenerate: bool,
+    pub smooth_normals_angle: Option<f32>,
+    pub recenter: bool,
+    pub scale_to_unit: bool,
+}
+
+impl MeshOptions {
+    pub fn new() -> Self {
+        MeshOptions {
+            weld_distance: None,
+            remove_degenerate: false,
+            smooth_normals_angle: None,
+            recenter: false,
+            scale_to_unit: false,
+        }
+    }
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        MeshOptions::new()
+    }
+}
+
+const DEGENERATE_AREA: f32 = 1e-10;
 
 pub struct Mesh {
     material: Material,
     triangles: Vec<Triangle>,
     bbox: BBox,
+    area_cdf: Vec<f32>,
     name: String,
 }
 
 impl Mesh {
     pub fn new(material: Material, triangles: Vec<Triangle>, name: String) -> Self {
         let bbox = BBox::new_from_triangles(&triangles);
+        let area_cdf = Mesh::create_area_cdf(&triangles);
 
         Mesh {
             name,
             triangles,
             material,
-            bbox
+            bbox,
+            area_cdf
+        }
+    }
+
+    // running sum of triangle areas used to pick triangles when sampling surface
+    fn create_area_cdf(triangles: &[Triangle]) -> Vec<f32> {
+        let mut area = 0.;
+
+        triangles.iter().map(|triangle| {
+            area += triangle.get_area();
+            area
+        }).collect()
+    }
+
+    fn update_bounds(&mut self) {
+        self.bbox = BBox::new_from_triangles(&self.triangles);
+        self.area_cdf = Mesh::create_area_cdf(&self.triangles);
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_triangles(&self) -> &Vec<Triangle> {
+        &self.triangles
+    }
+
+    pub fn get_bbox(&self) -> &BBox {
+        &self.bbox
+    }
+
+    pub fn process(&mut self, options: &MeshOptions) {
+        if let Some(distance) = options.weld_distance {
+            self.weld_vertices(distance);
+        }
+
+        if options.remove_degenerate {
+            self.remove_degenerate_triangles();
+        }
+
+        if options.recenter {
+            self.recenter();
+        }
+
+        if options.scale_to_unit {
+            self.scale_to_unit_size();
+        }
+
+        if let Some(angle) = options.smooth_normals_angle {
+            self.compute_normals(angle);
+        }
+    }
+
+    fn update_vertices<F>(&mut self, transform: F) where F: Fn(&Vector3<f32>) -> Vector3<f32> {
+        for triangle in &mut self.triangles {
+            let [v0, v1, v2] = triangle.get_vertices();
+            let (v0, v1, v2) = (transform(v0), transform(v1), transform(v2));
+
+            triangle.set_vertices(v0, v1, v2);
+        }
+
+        self.update_bounds();
+    }
+
+    // snaps every vertex to first vertex found within distance, grid cells are as large as distance
+    // so candidates are searched in neighbouring cells only
+    pub fn weld_vertices(&mut self, distance: f32) {
+        if distance <= 0. || !distance.is_finite() {
+            return;
+        }
+
+        let cell = |vertex: &Vector3<f32>| (
+            (vertex.x / distance).floor() as i64,
+            (vertex.y / distance).floor() as i64,
+            (vertex.z / distance).floor() as i64
+        );
+        let vertex_key = |vertex: &Vector3<f32>| [vertex.x.to_bits(), vertex.y.to_bits(), vertex.z.to_bits()];
+
+        let mut cells: HashMap<(i64, i64, i64), Vec<Vector3<f32>>> = HashMap::new();
+        let mut welded: HashMap<[u32; 3], Vector3<f32>> = HashMap::new();
+
+        for triangle in &self.triangles {
+            for vertex in &triangle.get_vertices() {
+                if welded.contains_key(&vertex_key(vertex)) {
+                    continue;
+                }
+
+                let (x, y, z) = cell(vertex);
+                let neighbour = (-1..=1).flat_map(|i| (-1..=1).flat_map(move |j| (-1..=1).map(move |k| (x + i, y + j, z + k))))
+                    .filter_map(|key| cells.get(&key))
+                    .flat_map(|candidates| candidates.iter())
+                    .find(|candidate| (*candidate - *vertex).magnitude() <= distance)
+                    .copied();
+
+                let target = match neighbour {
+                    Some(target) => target,
+                    None => {
+                        cells.entry((x, y, z)).or_default().push(**vertex);
+                        **vertex
+                    }
+                };
+
+                welded.insert(vertex_key(vertex), target);
+            }
+        }
+
+        self.update_vertices(|vertex| welded[&vertex_key(vertex)]);
+    }
+
+    pub fn remove_degenerate_triangles(&mut self) {
+        self.triangles.retain(|triangle| {
+            let [v0, v1, v2] = triangle.get_vertices();
+            triangle.get_area() > DEGENERATE_AREA && v0 != v1 && v1 != v2 && v0 != v2
+        });
+
+        self.update_bounds();
+    }
+
+    pub fn recenter(&mut self) {
+        let center = self.bbox.get_center();
+        self.update_vertices(|vertex| vertex - &center);
+    }
+
+    // uniformly scales mesh so that its largest bounding box side equals one
+    pub fn scale_to_unit_size(&mut self) {
+        let size = self.bbox.get_size();
+        let max_side = f32::max(size.x, f32::max(size.y, size.z));
+
+        if max_side <= 0. || !max_side.is_finite() {
+            return;
+        }
+
+        let scale = 1. / max_side;
+        self.update_vertices(|vertex| *vertex * scale);
+    }
+
+    // area weighted vertex normals, faces meeting at angle above crease angle (degrees) stay sharp
+    pub fn compute_normals(&mut self, crease_angle: f32) {
+        let cos_crease = f32::cos(crease_angle.to_radians());
+        let mut faces_by_vertex: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+
+        let vertex_key = |vertex: &Vector3<f32>| [vertex.x.to_bits(), vertex.y.to_bits(), vertex.z.to_bits()];
+
+        for (i, triangle) in self.triangles.iter().enumerate() {
+            for vertex in &triangle.get_vertices() {
+                faces_by_vertex.entry(vertex_key(vertex)).or_default().push(i);
+            }
+        }
+
+        let normals: Vec<[Vector3<f32>; 3]> = self.triangles.iter().map(|triangle| {
+            let face_normal = triangle.get_normal();
+            let vertices = triangle.get_vertices();
+
+            let vertex_normal = |vertex: &Vector3<f32>| {
+                let mut normal = Vector3::new(0., 0., 0.);
+
+                for face in &faces_by_vertex[&vertex_key(vertex)] {
+                    let neighbour = &self.triangles[*face];
+                    let neighbour_normal = neighbour.get_normal();
+
+                    if neighbour_normal.dot(&face_normal) >= cos_crease {
+                        normal = &normal + &(neighbour_normal * neighbour.get_area());
+                    }
+                }
+
+                if normal.magnitude() > 0. {
+                    normal.normalize();
+                    normal
+                } else {
+                    face_normal
+                }
+            };
+
+            [vertex_normal(vertices[0]), vertex_normal(vertices[1]), vertex_normal(vertices[2])]
+        }).collect();
+
+        for (triangle, normals) in self.triangles.iter_mut().zip(normals) {
+            triangle.set_normals(normals);
         }
     }
+
+    // tangents along increasing u texture coordinate at vertices of every triangle, for exporters and
+    // shading models that need tangent frame
+    pub fn compute_tangents(&self) -> Vec<[Vector3<f32>; 3]> {
+        self.triangles.iter().map(|triangle| {
+            let [v0, v1, v2] = triangle.get_vertices();
+            let uvs = triangle.get_uvs();
+
+            let edge1 = v1 - v0;
+            let edge2 = v2 - v0;
+            let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+            let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+
+            let determinant = du1 * dv2 - du2 * dv1;
+
+            let mut tangent = if determinant.abs() > f32::EPSILON {
+                &(edge1 * (dv2 / determinant)) - &(edge2 * (dv1 / determinant))
+            } else {
+                edge1
+            };
+            tangent.normalize();
+
+            let tangents = [0, 1, 2].iter().map(|i| {
+                // Gram-Schmidt against shading normal at the vertex
+                let normal = match i {
+                    0 => triangle.get_shading_normal(0., 0.),
+                    1 => triangle.get_shading_normal(1., 0.),
+                    _ => triangle.get_shading_normal(0., 1.)
+                };
+
+                let mut orthogonal = &tangent - &(normal * normal.dot(&tangent));
+                orthogonal.normalize();
+                orthogonal
+            }).collect::<Vec<Vector3<f32>>>();
+
+            [tangents[0], tangents[1], tangents[2]]
+        }).collect()
+    }
+
+    pub fn get_surface_area(&self) -> f32 {
+        self.area_cdf.last().copied().unwrap_or(0.)
+    }
+
+    // volume enclosed by closed mesh, computed as sum of signed tetrahedra volumes
+    pub fn get_volume(&self) -> f32 {
+        let volume: f32 = self.triangles.iter().map(|triangle| {
+            let [v0, v1, v2] = triangle.get_vertices();
+            let mut cross = *v1;
+            cross.cross(v2);
+
+            v0.dot(&cross) / 6.
+        }).sum();
+
+        volume.abs()
+    }
 }
 
 impl Renderable for Mesh {
@@ -32,31 +298,75 @@ impl Renderable for Mesh {
 
         let mut min_distance = std::f32::MAX;
         let mut intersected_triangle: Option<&Triangle> = None;
+        let mut barycentric = (0., 0.);
 
         for triangle in &self.triangles {
-            match triangle.intersects(ray) {
-                Some(distance) => {
-                    if min_distance > distance {
-                        intersected_triangle = Some(triangle);
-                        min_distance = distance;
-                    }
-                },
-                _ => {},
-            };
+            if let Some((distance, u, v)) = triangle.intersects_barycentric(ray) {
+                if min_distance > distance {
+                    intersected_triangle = Some(triangle);
+                    min_distance = distance;
+                    barycentric = (u, v);
+                }
+            }
         }
 
         return match intersected_triangle {
             Some(triangle) => {
                 Some(IntersectionData {
                     distance: min_distance,
-                    normal: triangle.get_normal()
+                    normal: triangle.get_shading_normal(barycentric.0, barycentric.1),
+                    uv: triangle.get_uv(barycentric.0, barycentric.1),
+                    vertex_color: triangle.get_color(barycentric.0, barycentric.1)
                 })
             }
             _ => None
         }
     }
 
+    // triangle is picked proportionally to its area, r1 is rescaled to be reused inside of it
+    fn sample_surface(&self, r1: f32, r2: f32, _time: f32) -> Option<(Vector3<f32>, Vector3<f32>, f32)> {
+        let area = self.get_surface_area();
+
+        if area <= 0. {
+            return None;
+        }
+
+        let target = r1 * area;
+        let index = usize::min(self.area_cdf.partition_point(|sum| *sum <= target), self.triangles.len() - 1);
+        let start = if index > 0 { self.area_cdf[index - 1] } else { 0. };
+        let triangle = &self.triangles[index];
+
+        let r1 = ((target - start) / triangle.get_area()).clamp(0., 1.);
+        Some((triangle.sample_point(r1, r2), triangle.get_normal(), area))
+    }
+
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        if !self.bbox.ray_intersect_box(ray) {
+            return Vec::new();
+        }
+
+        let mut intersections: Vec<IntersectionData> = self.triangles.iter().filter_map(|triangle| {
+            triangle.intersects_barycentric(ray).map(|(distance, u, v)| IntersectionData {
+                distance,
+                normal: triangle.get_shading_normal(u, v),
+                uv: triangle.get_uv(u, v),
+                vertex_color: triangle.get_color(u, v)
+            })
+        }).collect();
+
+        sort_intersections(&mut intersections);
+        intersections
+    }
+
     fn get_material(&self) -> &Material {
         &self.material
     }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        Some(self.bbox)
+    }
 }
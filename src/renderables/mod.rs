@@ -1,7 +1,18 @@
 pub mod material;
 pub mod renderable;
+pub mod texture;
 
 pub mod sphere;
 pub mod triangle;
 pub mod mesh;
 pub mod plane;
+pub mod instance;
+pub mod cuboid;
+pub mod rectangle;
+pub mod disk;
+pub mod cylinder;
+pub mod cone;
+pub mod torus;
+pub mod capsule;
+pub mod csg;
+pub mod sdf;
@@ -34,7 +34,9 @@ impl Renderable for Plane {
             if t > std::f32::EPSILON {
                 return Some(IntersectionData{
                     distance: t,
-                    normal: self.get_normal()
+                    normal: self.get_normal(),
+                    uv: (0., 0.),
+                    vertex_color: None
                 });
             }
         }
@@ -45,4 +47,8 @@ impl Renderable for Plane {
     fn get_material(&self) -> &Material {
         &self.material
     }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
 }
@@ -0,0 +1,105 @@
+use crate::renderables::renderable::{Renderable, IntersectionData};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+
+const EPSILON: f32 = 0.00001;
+
+// parallelogram spanned by two edges from a corner, rectangle when edges are perpendicular
+pub struct Rectangle {
+    material: Material,
+    corner: Vector3<f32>,
+    edge_u: Vector3<f32>,
+    edge_v: Vector3<f32>,
+    normal: Vector3<f32>,
+    w: Vector3<f32>,
+}
+
+impl Rectangle {
+    pub fn new(corner: Vector3<f32>, edge_u: Vector3<f32>, edge_v: Vector3<f32>, material: Material) -> Self {
+        let mut n = edge_u;
+        n.cross(&edge_v);
+
+        let w = n * (1. / n.dot(&n));
+
+        let mut normal = n;
+        normal.normalize();
+
+        Rectangle {
+            material,
+            corner,
+            edge_u,
+            edge_v,
+            normal,
+            w
+        }
+    }
+
+    pub fn get_normal(&self) -> Vector3<f32> {
+        self.normal
+    }
+}
+
+impl Renderable for Rectangle {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        let denominator = self.normal.dot(&ray.direction);
+
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let t = (&self.corner - &ray.origin).dot(&self.normal) / denominator;
+
+        if t < EPSILON {
+            return None;
+        }
+
+        let planar_hit = &(&ray.origin + &(ray.direction * t)) - &self.corner;
+
+        let mut hit_cross_v = planar_hit;
+        hit_cross_v.cross(&self.edge_v);
+        let alpha = self.w.dot(&hit_cross_v);
+
+        let mut u_cross_hit = self.edge_u;
+        u_cross_hit.cross(&planar_hit);
+        let beta = self.w.dot(&u_cross_hit);
+
+        if !(0. ..=1.).contains(&alpha) || !(0. ..=1.).contains(&beta) {
+            return None;
+        }
+
+        Some(IntersectionData {
+            distance: t,
+            normal: self.normal,
+            uv: (alpha, beta),
+            vertex_color: None
+        })
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    fn sample_surface(&self, r1: f32, r2: f32, _time: f32) -> Option<(Vector3<f32>, Vector3<f32>, f32)> {
+        let mut n = self.edge_u;
+        n.cross(&self.edge_v);
+
+        Some((&(&self.corner + &(self.edge_u * r1)) + &(self.edge_v * r2), self.normal, n.magnitude()))
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let far_corner = &(&self.corner + &self.edge_u) + &self.edge_v;
+
+        Some(BBox::new_from_points(&[
+            self.corner,
+            &self.corner + &self.edge_u,
+            &self.corner + &self.edge_v,
+            far_corner
+        ]))
+    }
+}
@@ -1,15 +1,47 @@
 use crate::math::ray::Ray;
 use crate::renderables::material::Material;
 use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+use crate::math::bbox::BBox;
 
 pub struct IntersectionData {
     pub distance: f32,
     pub normal: Vector3<f32>,
+    pub uv: (f32, f32),
+    pub vertex_color: Option<Color>,
+}
+
+const EPSILON: f32 = 0.00001;
+
+// drops hits behind ray origin and orders the rest by distance
+pub fn sort_intersections(intersections: &mut Vec<IntersectionData>) {
+    intersections.retain(|intersection| intersection.distance > EPSILON);
+    intersections.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(std::cmp::Ordering::Equal));
 }
 
 pub trait Renderable : Sync {
     fn intersects(&self, ray: &Ray) -> Option<IntersectionData>;
     fn get_material(&self) -> &Material;
+
+    // None when material is shared with other renderables
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        None
+    }
+
+    // every surface crossing along the ray ordered by distance, used for solid operations
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        self.intersects(ray).into_iter().collect()
+    }
+
+    // unbounded renderables such as planes return None
+    fn get_bbox(&self) -> Option<BBox> {
+        None
+    }
+
+    // uniformly distributed point with its normal and total surface area, used to emit light from emissive surfaces
+    fn sample_surface(&self, _r1: f32, _r2: f32, _time: f32) -> Option<(Vector3<f32>, Vector3<f32>, f32)> {
+        None
+    }
 }
 
 impl PartialEq for Box<dyn Renderable + Send> {
@@ -0,0 +1,265 @@
+use crate::renderables::renderable::{Renderable, IntersectionData};
+use crate::renderables::material::Material;
+use crate::renderables::sphere::Sphere;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+
+const DEFAULT_MAX_STEPS: usize = 256;
+const DEFAULT_EPSILON: f32 = 0.00001;
+
+pub type DistanceFunction = Box<dyn Fn(&Vector3<f32>) -> f32 + Send + Sync>;
+
+// surface defined by zero level of signed distance function, rendered by sphere tracing inside bbox
+pub struct Sdf {
+    material: Material,
+    distance: DistanceFunction,
+    bbox: BBox,
+    max_steps: usize,
+    epsilon: f32,
+    step_scale: f32,
+}
+
+impl Sdf {
+    pub fn new<F>(distance: F, bbox: BBox, material: Material) -> Self where F: Fn(&Vector3<f32>) -> f32 + Send + Sync + 'static {
+        Sdf {
+            material,
+            distance: Box::new(distance),
+            bbox,
+            max_steps: DEFAULT_MAX_STEPS,
+            epsilon: DEFAULT_EPSILON,
+            step_scale: 1.
+        }
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = max_steps;
+    }
+
+    pub fn set_epsilon(&mut self, epsilon: f32) {
+        self.epsilon = epsilon;
+    }
+
+    // epsilon grows with distance from origin and along the ray, where float precision is lower
+    fn get_tolerance(&self, point: &Vector3<f32>, t: f32) -> f32 {
+        self.epsilon * f32::max(1., f32::max(t, point.magnitude()))
+    }
+
+    // distorted fields such as twist overestimate distance and need steps below one
+    pub fn set_step_scale(&mut self, step_scale: f32) {
+        self.step_scale = step_scale;
+    }
+
+    pub fn get_distance(&self, point: &Vector3<f32>) -> f32 {
+        (self.distance)(point)
+    }
+
+    // gradient by tetrahedron of central differences
+    pub fn get_normal(&self, point: &Vector3<f32>) -> Vector3<f32> {
+        let h = self.get_tolerance(point, 0.) * 10.;
+
+        let offsets = [
+            Vector3::new(1., -1., -1.),
+            Vector3::new(-1., -1., 1.),
+            Vector3::new(-1., 1., -1.),
+            Vector3::new(1., 1., 1.)
+        ];
+
+        let mut normal = Vector3::new(0., 0., 0.);
+
+        for offset in &offsets {
+            let distance = self.get_distance(&(point + &(*offset * h)));
+            normal = &normal + &(*offset * distance);
+        }
+
+        normal.normalize();
+        normal
+    }
+
+    // distance along the ray of first surface crossing after start, None when ray leaves the bbox first
+    fn march(&self, ray: &Ray, start: f32, t_far: f32) -> Option<f32> {
+        let min_distance = self.get_tolerance(&ray.origin, 0.) * 10.;
+
+        let mut t = start;
+
+        for _ in 0..self.max_steps {
+            if t > t_far {
+                return None;
+            }
+
+            let point = &ray.origin + &(ray.direction * t);
+            let distance = self.get_distance(&point).abs();
+            let tolerance = self.get_tolerance(&point, t);
+
+            // hits right at the origin belong to surface the ray was spawned from
+            if distance < tolerance && t > min_distance {
+                return Some(t);
+            }
+
+            t += f32::max(distance * self.step_scale, tolerance);
+        }
+
+        None
+    }
+
+    // steps along the ray until it leaves tolerance band around surface hit at t
+    fn leave_surface(&self, ray: &Ray, t: f32) -> f32 {
+        let mut t = t;
+
+        for _ in 0..self.max_steps {
+            let point = &ray.origin + &(ray.direction * t);
+            let tolerance = self.get_tolerance(&point, t);
+
+            if self.get_distance(&point).abs() >= tolerance {
+                break;
+            }
+
+            t += tolerance;
+        }
+
+        t
+    }
+
+    fn create_intersection(&self, ray: &Ray, t: f32) -> IntersectionData {
+        let normal = self.get_normal(&(&ray.origin + &(ray.direction * t)));
+
+        IntersectionData {
+            distance: t,
+            normal,
+            uv: Sphere::get_uv(&normal),
+            vertex_color: None
+        }
+    }
+}
+
+impl Renderable for Sdf {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        let (t_near, t_far) = self.bbox.ray_interval(ray)?;
+
+        self.march(ray, f32::max(t_near, 0.), t_far).map(|t| self.create_intersection(ray, t))
+    }
+
+    // marching continues past every hit, so entries and exits are reported for solid operations
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        let mut intersections = Vec::new();
+
+        let (t_near, t_far) = match self.bbox.ray_interval(ray) {
+            Some(interval) => interval,
+            None => return intersections
+        };
+
+        let mut t = f32::max(t_near, 0.);
+
+        while let Some(hit) = self.march(ray, t, t_far) {
+            intersections.push(self.create_intersection(ray, hit));
+            t = self.leave_surface(ray, hit);
+        }
+
+        intersections
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        Some(self.bbox)
+    }
+}
+
+pub fn sphere(point: &Vector3<f32>, radius: f32) -> f32 {
+    point.magnitude() - radius
+}
+
+pub fn cuboid(point: &Vector3<f32>, half_size: &Vector3<f32>) -> f32 {
+    let q = Vector3::new(point.x.abs() - half_size.x, point.y.abs() - half_size.y, point.z.abs() - half_size.z);
+    let outside = Vector3::new(f32::max(q.x, 0.), f32::max(q.y, 0.), f32::max(q.z, 0.));
+
+    outside.magnitude() + f32::min(f32::max(q.x, f32::max(q.y, q.z)), 0.)
+}
+
+// torus lying in xz plane
+pub fn torus(point: &Vector3<f32>, major_radius: f32, minor_radius: f32) -> f32 {
+    let ring = f32::sqrt(point.x * point.x + point.z * point.z) - major_radius;
+
+    f32::sqrt(ring * ring + point.y * point.y) - minor_radius
+}
+
+pub fn capsule(point: &Vector3<f32>, start: &Vector3<f32>, end: &Vector3<f32>, radius: f32) -> f32 {
+    let pa = point - start;
+    let ba = end - start;
+    let h = (pa.dot(&ba) / ba.dot(&ba)).clamp(0., 1.);
+
+    (&pa - &(ba * h)).magnitude() - radius
+}
+
+pub fn plane(point: &Vector3<f32>, normal: &Vector3<f32>, offset: f32) -> f32 {
+    point.dot(normal) + offset
+}
+
+// polynomial smooth minimum, k controls blend radius
+pub fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
+    if k <= 0. {
+        return f32::min(a, b);
+    }
+
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0., 1.);
+
+    b + (a - b) * h - k * h * (1. - h)
+}
+
+pub fn smooth_max(a: f32, b: f32, k: f32) -> f32 {
+    -smooth_min(-a, -b, k)
+}
+
+// infinite domain repetition, zero period component disables repetition along axis
+pub fn repeat(point: &Vector3<f32>, period: &Vector3<f32>) -> Vector3<f32> {
+    let wrap = |value: f32, period: f32| {
+        if period > 0. {
+            value - period * (value / period).round()
+        } else {
+            value
+        }
+    };
+
+    Vector3::new(wrap(point.x, period.x), wrap(point.y, period.y), wrap(point.z, period.z))
+}
+
+// rotates xz plane proportionally to height, amount in radians per unit
+pub fn twist(point: &Vector3<f32>, amount: f32) -> Vector3<f32> {
+    let angle = amount * point.y;
+    let (sin, cos) = angle.sin_cos();
+
+    Vector3::new(cos * point.x - sin * point.z, point.y, sin * point.x + cos * point.z)
+}
+
+pub fn mandelbulb(point: &Vector3<f32>, power: f32, iterations: usize) -> f32 {
+    let mut z = *point;
+    let mut dr = 1.;
+    let mut r = 0.;
+
+    for _ in 0..iterations {
+        r = z.magnitude();
+
+        if r > 2. || r == 0. {
+            break;
+        }
+
+        let theta = f32::acos((z.z / r).clamp(-1., 1.)) * power;
+        let phi = f32::atan2(z.y, z.x) * power;
+        dr = r.powf(power - 1.) * power * dr + 1.;
+
+        let zr = r.powf(power);
+        z = &(Vector3::new(theta.sin() * phi.cos(), phi.sin() * theta.sin(), theta.cos()) * zr) + point;
+    }
+
+    if r == 0. {
+        return 0.;
+    }
+
+    0.5 * r.ln() * r / dr
+}
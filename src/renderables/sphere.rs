@@ -1,12 +1,16 @@
-use crate::renderables::renderable::{Renderable, IntersectionData};
+use crate::renderables::renderable::{Renderable, IntersectionData, sort_intersections};
 use crate::math::ray::Ray;
 use crate::math::vec3::Vector3;
 use crate::renderables::material::Material;
+use crate::math::bbox::BBox;
+use crate::math::solve_quadratic;
+use crate::math::animation::Track;
 
 pub struct Sphere {
     radius: f32,
     material: Material,
-    position: Vector3<f32>
+    position: Vector3<f32>,
+    position_track: Option<Track<Vector3<f32>>>,
 }
 
 impl Sphere {
@@ -14,10 +18,27 @@ impl Sphere {
         Sphere {
             radius,
             material,
-            position
+            position,
+            position_track: None
         }
     }
 
+    // moving sphere, center follows track over ray time
+    pub fn set_position_track(&mut self, track: Track<Vector3<f32>>) {
+        self.position_track = Some(track);
+    }
+
+    pub fn get_position_at(&self, time: f32) -> Vector3<f32> {
+        self.position_track.as_ref().and_then(|track| track.sample(time)).unwrap_or(self.position)
+    }
+
+    pub fn get_uv(normal: &Vector3<f32>) -> (f32, f32) {
+        let u = 0.5 + f32::atan2(normal.z, normal.x) / (2. * std::f32::consts::PI);
+        let v = 0.5 - f32::asin(normal.y) / std::f32::consts::PI;
+
+        (u, v)
+    }
+
     pub fn get_normal(&self, hit: &Vector3<f32>) -> Vector3<f32> {
         let mut normal = hit - &self.position;
         normal.normalize();
@@ -27,40 +48,60 @@ impl Sphere {
 
 impl Renderable for Sphere {
     fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
-        let from_center_to_camera = &self.position - &ray.origin;
-        let projection_length = from_center_to_camera.dot(&ray.direction);
+        self.get_intersections(ray).into_iter().next()
+    }
 
-        if projection_length < 0. {
-            return None;
-        }
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        let position = self.get_position_at(ray.time);
+        let from_center_to_origin = &ray.origin - &position;
 
-        let from_center_to_point = from_center_to_camera.dot(&from_center_to_camera) - (projection_length * projection_length);
+        let b = 2. * from_center_to_origin.dot(&ray.direction);
+        let c = from_center_to_origin.dot(&from_center_to_origin) - self.radius * self.radius;
 
-        let quad_radius = self.radius * self.radius;
+        let mut intersections: Vec<IntersectionData> = solve_quadratic(ray.direction.dot(&ray.direction), b, c).into_iter().map(|distance| {
+            let mut normal = &(&ray.origin + &(ray.direction * distance)) - &position;
+            normal.normalize();
 
-        if from_center_to_point > quad_radius {
-            return None;
-        }
+            IntersectionData {
+                distance,
+                normal,
+                uv: Sphere::get_uv(&normal),
+                vertex_color: None
+            }
+        }).collect();
+
+        sort_intersections(&mut intersections);
+        intersections
+    }
 
-        let from_center_to_sphere_end = f32::sqrt(quad_radius - from_center_to_point);
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
 
-        let first_intersection_distance = projection_length - from_center_to_sphere_end;
-        let second_intersection_distance = projection_length + from_center_to_sphere_end;
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
 
-        let distance;
-        if first_intersection_distance < second_intersection_distance {
-           distance = first_intersection_distance;
-        } else {
-            distance = second_intersection_distance;
-        }
+    fn sample_surface(&self, r1: f32, r2: f32, time: f32) -> Option<(Vector3<f32>, Vector3<f32>, f32)> {
+        let z = 1. - 2. * r1;
+        let radius = f32::sqrt(f32::max(0., 1. - z * z));
+        let phi = 2. * std::f32::consts::PI * r2;
+        let normal = Vector3::new(radius * f32::cos(phi), radius * f32::sin(phi), z);
 
-        Some(IntersectionData {
-            distance,
-            normal: self.get_normal( &(&ray.origin + &(ray.direction * distance)))
-        })
+        Some((&self.get_position_at(time) + &(normal * self.radius), normal, 4. * std::f32::consts::PI * self.radius * self.radius))
     }
 
-    fn get_material(&self) -> &Material {
-        &self.material
+    fn get_bbox(&self) -> Option<BBox> {
+        let radius = Vector3::new(self.radius, self.radius, self.radius);
+        let mut bbox = BBox::new(&self.position - &radius, &self.position + &radius);
+
+        // linear motion between keyframes keeps sphere inside hull of keyframe spheres
+        if let Some(track) = &self.position_track {
+            for keyframe in track.get_keyframes() {
+                bbox = bbox.union(&BBox::new(&keyframe.value - &radius, &keyframe.value + &radius));
+            }
+        }
+
+        Some(bbox)
     }
 }
@@ -0,0 +1,38 @@
+use crate::math::color::Color;
+
+#[derive(Debug)]
+pub struct Texture {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Texture {
+    pub fn new(width: u32, height: u32, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), (width * height) as usize);
+
+        Texture {
+            width,
+            height,
+            pixels
+        }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        let u = u - u.floor();
+        let v = v - v.floor();
+
+        let x = usize::min((u * self.width as f32) as usize, self.width as usize - 1);
+        let y = usize::min((v * self.height as f32) as usize, self.height as usize - 1);
+
+        self.pixels[y * self.width as usize + x]
+    }
+}
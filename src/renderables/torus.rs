@@ -0,0 +1,144 @@
+use crate::renderables::renderable::{Renderable, IntersectionData, sort_intersections};
+use crate::renderables::material::Material;
+use crate::math::ray::Ray;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+use crate::math::frame::Frame;
+use crate::math::solve_quadratic;
+use std::f32::consts::PI;
+
+const MIN_STEPS: usize = 32;
+const MAX_STEPS: usize = 1024;
+const BISECTION_STEPS: usize = 40;
+
+// torus lying in plane perpendicular to axis
+pub struct Torus {
+    material: Material,
+    frame: Frame,
+    major_radius: f32,
+    minor_radius: f32,
+}
+
+impl Torus {
+    pub fn new(center: Vector3<f32>, axis: Vector3<f32>, major_radius: f32, minor_radius: f32, material: Material) -> Self {
+        Torus {
+            material,
+            frame: Frame::new(center, axis),
+            major_radius,
+            minor_radius
+        }
+    }
+
+    // implicit quartic (|p|^2 + R^2 - r^2)^2 - 4R^2(x^2 + y^2) along local ray
+    fn evaluate(&self, o: &Vector3<f32>, d: &Vector3<f32>, t: f64) -> f64 {
+        let (x, y, z) = (o.x as f64 + d.x as f64 * t, o.y as f64 + d.y as f64 * t, o.z as f64 + d.z as f64 * t);
+        let major2 = (self.major_radius as f64).powi(2);
+        let minor2 = (self.minor_radius as f64).powi(2);
+
+        let sum = x * x + y * y + z * z + major2 - minor2;
+        sum * sum - 4. * major2 * (x * x + y * y)
+    }
+
+    fn create_intersection(&self, hit: &Vector3<f32>, distance: f32) -> IntersectionData {
+        let mut ring = Vector3::new(hit.x, hit.y, 0.);
+        ring.normalize();
+        let ring_point = ring * self.major_radius;
+
+        let mut normal = hit - &ring_point;
+        normal.normalize();
+
+        let distance_to_axis = f32::sqrt(hit.x * hit.x + hit.y * hit.y);
+
+        IntersectionData {
+            distance,
+            normal: self.frame.to_world_direction(&normal),
+            uv: (
+                f32::atan2(hit.y, hit.x) / (2. * PI) + 0.5,
+                f32::atan2(hit.z, distance_to_axis - self.major_radius) / (2. * PI) + 0.5
+            ),
+            vertex_color: None
+        }
+    }
+}
+
+impl Renderable for Torus {
+    fn intersects(&self, ray: &Ray) -> Option<IntersectionData> {
+        self.get_intersections(ray).into_iter().next()
+    }
+
+    fn get_intersections(&self, ray: &Ray) -> Vec<IntersectionData> {
+        let local_ray = self.frame.to_local_ray(ray);
+        let (o, d) = (&local_ray.origin, &local_ray.direction);
+        let bounding_radius = self.major_radius + self.minor_radius;
+
+        // roots are searched only inside bounding sphere
+        let (t_min, t_max) = match solve_quadratic(d.dot(d), 2. * o.dot(d), o.dot(o) - bounding_radius * bounding_radius)[..] {
+            [t_min, t_max] => (t_min, t_max),
+            _ => return Vec::new()
+        };
+
+        let t_min = f32::max(t_min, 0.) as f64;
+        let t_max = t_max as f64;
+
+        if t_max <= t_min {
+            return Vec::new();
+        }
+
+        let steps = (((t_max - t_min) / (self.minor_radius as f64 * 0.25)).ceil() as usize).clamp(MIN_STEPS, MAX_STEPS);
+        let step = (t_max - t_min) / steps as f64;
+
+        let mut intersections = Vec::new();
+        let mut previous_t = t_min;
+        let mut previous_value = self.evaluate(o, d, previous_t);
+
+        for i in 1..=steps {
+            let t = t_min + step * i as f64;
+            let value = self.evaluate(o, d, t);
+
+            if previous_value.signum() != value.signum() {
+                let (mut low, mut high) = (previous_t, t);
+                let low_sign = previous_value.signum();
+
+                for _ in 0..BISECTION_STEPS {
+                    let middle = (low + high) * 0.5;
+
+                    if self.evaluate(o, d, middle).signum() == low_sign {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+
+                let distance = ((low + high) * 0.5) as f32;
+                intersections.push(self.create_intersection(&(o + &(*d * distance)), distance));
+            }
+
+            previous_t = t;
+            previous_value = value;
+        }
+
+        sort_intersections(&mut intersections);
+        intersections
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+
+    fn get_material_mut(&mut self) -> Option<&mut Material> {
+        Some(&mut self.material)
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let axis = &self.frame.z;
+        let (major, minor) = (self.major_radius, self.minor_radius);
+
+        let extent = Vector3::new(
+            major * f32::sqrt(f32::max(0., 1. - axis.x * axis.x)) + minor,
+            major * f32::sqrt(f32::max(0., 1. - axis.y * axis.y)) + minor,
+            major * f32::sqrt(f32::max(0., 1. - axis.z * axis.z)) + minor
+        );
+
+        Some(BBox::new(&self.frame.origin - &extent, &self.frame.origin + &extent))
+    }
+}
@@ -1,5 +1,6 @@
 use crate::math::ray::Ray;
 use crate::math::vec3::Vector3;
+use crate::math::color::Color;
 
 pub struct Triangle {
     v0: Vector3<f32>,
@@ -8,12 +9,19 @@ pub struct Triangle {
     edge1: Vector3<f32>,
     edge2: Vector3<f32>,
     normal: Vector3<f32>,
+    uvs: [(f32, f32); 3],
+    colors: Option<[Color; 3]>,
+    normals: Option<[Vector3<f32>; 3]>,
 }
 
 const EPSILON: f32 = 0.00001;
 
 impl Triangle {
     pub fn new(v0: Vector3<f32>, v1: Vector3<f32>, v2: Vector3<f32>) -> Self {
+        Triangle::with_uvs(v0, v1, v2, [(0., 0.), (1., 0.), (0., 1.)])
+    }
+
+    pub fn with_uvs(v0: Vector3<f32>, v1: Vector3<f32>, v2: Vector3<f32>, uvs: [(f32, f32); 3]) -> Self {
         let edge1 = &v1.clone() - &v0;
         let edge2 = &v2.clone() - &v0;
 
@@ -30,9 +38,20 @@ impl Triangle {
             edge1,
             edge2,
             normal,
+            uvs,
+            colors: None,
+            normals: None,
         }
     }
 
+    pub fn set_vertices(&mut self, v0: Vector3<f32>, v1: Vector3<f32>, v2: Vector3<f32>) {
+        let mut triangle = Triangle::with_uvs(v0, v1, v2, self.uvs);
+        triangle.colors = self.colors;
+        triangle.normals = self.normals;
+
+        *self = triangle;
+    }
+
     pub fn get_vertices(&self) -> [&Vector3<f32>; 3] {
         [&self.v0, &self.v1, &self.v2]
     }
@@ -41,8 +60,62 @@ impl Triangle {
         self.normal.clone()
     }
 
+    pub fn get_area(&self) -> f32 {
+        let mut cross = self.edge1;
+        cross.cross(&self.edge2);
+
+        cross.magnitude() * 0.5
+    }
+
+    pub fn sample_point(&self, r1: f32, r2: f32) -> Vector3<f32> {
+        let r = f32::sqrt(r1);
+
+        &(&self.v0 + &(self.edge1 * ((1. - r2) * r))) + &(self.edge2 * (r2 * r))
+    }
+
+    pub fn get_uvs(&self) -> &[(f32, f32); 3] {
+        &self.uvs
+    }
+
+    pub fn set_normals(&mut self, normals: [Vector3<f32>; 3]) {
+        self.normals = Some(normals);
+    }
+
+    // interpolated vertex normal if present, face normal otherwise
+    pub fn get_shading_normal(&self, u: f32, v: f32) -> Vector3<f32> {
+        match &self.normals {
+            Some(normals) => {
+                let mut normal = &(&(normals[0] * (1. - u - v)) + &(normals[1] * u)) + &(normals[2] * v);
+                normal.normalize();
+                normal
+            },
+            None => self.get_normal()
+        }
+    }
+
+    pub fn get_uv(&self, u: f32, v: f32) -> (f32, f32) {
+        let w = 1. - u - v;
+
+        (
+            self.uvs[0].0 * w + self.uvs[1].0 * u + self.uvs[2].0 * v,
+            self.uvs[0].1 * w + self.uvs[1].1 * u + self.uvs[2].1 * v
+        )
+    }
+
+    pub fn set_colors(&mut self, colors: [Color; 3]) {
+        self.colors = Some(colors);
+    }
+
+    pub fn get_color(&self, u: f32, v: f32) -> Option<Color> {
+        self.colors.map(|colors| colors[0] * (1. - u - v) + &(colors[1] * u) + &(colors[2] * v))
+    }
 
     pub fn intersects(&self, ray: &Ray) -> Option<f32> {
+        self.intersects_barycentric(ray).map(|(t, _, _)| t)
+    }
+
+    // returns distance and barycentric coordinates of the hit
+    pub fn intersects_barycentric(&self, ray: &Ray) -> Option<(f32, f32, f32)> {
         let mut pvec = ray.direction.clone();
 
         let h = pvec.cross(&self.edge2);
@@ -71,7 +144,7 @@ impl Triangle {
         let t = f * self.edge2.dot(q);
 
         if t > EPSILON {
-            return Some(t);
+            return Some((t, u, v));
         }
 
         return None;
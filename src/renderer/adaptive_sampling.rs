@@ -0,0 +1,90 @@
+// luminance offset on 0..255 scale keeping relative error of nearly black pixels bounded
+const DARK_OFFSET: f32 = 2.55;
+
+// distributes one frame worth of samples over pixels by their estimated relative error, pixels
+// whose error fell under noise threshold stop receiving samples
+pub struct AdaptiveSampling {
+    noise_threshold: f32,
+    min_samples: u32,
+    max_samples: u32,
+}
+
+impl AdaptiveSampling {
+    pub fn new(noise_threshold: f32) -> Self {
+        AdaptiveSampling {
+            noise_threshold,
+            min_samples: 16,
+            max_samples: 16
+        }
+    }
+
+    pub fn get_noise_threshold(&self) -> f32 {
+        self.noise_threshold
+    }
+
+    // samples every pixel receives before its variance estimate is trusted
+    pub fn set_min_samples(&mut self, min_samples: u32) {
+        self.min_samples = min_samples;
+    }
+
+    // limit of samples single pixel gets in one frame
+    pub fn set_max_samples(&mut self, max_samples: u32) {
+        self.max_samples = max_samples;
+    }
+
+    // standard error of mean luminance relative to the mean
+    pub fn get_error(mean: f32, mean_square: f32, count: u32) -> f32 {
+        if count < 2 {
+            return f32::INFINITY;
+        }
+
+        let count = count as f32;
+        let variance = f32::max(mean_square - mean * mean, 0.) * count / (count - 1.);
+
+        f32::sqrt(variance / count) / (mean + DARK_OFFSET)
+    }
+
+    pub fn is_converged(&self, mean: f32, mean_square: f32, count: u32) -> bool {
+        count >= self.min_samples && AdaptiveSampling::get_error(mean, mean_square, count) < self.noise_threshold
+    }
+
+    // fills samples of given pixels for next frame, budget is one sample per given pixel, returns count of
+    // pixels that still need samples
+    pub fn plan(&self, pixels: &[usize], luminance: &[f32], luminance_squares: &[f32], counts: &[u32], pixel_samples: &mut [u32]) -> usize {
+        let mut budget = pixels.len() as f32;
+        let mut noisy_error = 0.;
+        let mut active = 0;
+
+        for &pixel in pixels {
+            pixel_samples[pixel] = if counts[pixel] < self.min_samples {
+                budget -= 1.;
+                1
+            } else if self.is_converged(luminance[pixel], luminance_squares[pixel], counts[pixel]) {
+                0
+            } else {
+                noisy_error += AdaptiveSampling::get_error(luminance[pixel], luminance_squares[pixel], counts[pixel]);
+                u32::MAX
+            };
+
+            if pixel_samples[pixel] > 0 {
+                active += 1;
+            }
+        }
+
+        budget = f32::max(budget, 0.);
+
+        // noisy pixels share what is left proportionally to their error, rounded randomly
+        for &pixel in pixels {
+            if pixel_samples[pixel] != u32::MAX {
+                continue;
+            }
+
+            let share = budget * AdaptiveSampling::get_error(luminance[pixel], luminance_squares[pixel], counts[pixel]) / noisy_error;
+            let samples = share.floor() as u32 + if rand::random::<f32>() < share.fract() { 1 } else { 0 };
+
+            pixel_samples[pixel] = u32::min(u32::max(samples, 1), self.max_samples);
+        }
+
+        active
+    }
+}
@@ -0,0 +1,192 @@
+use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+use crate::renderables::material::Material;
+use std::sync::Arc;
+
+// ids stay exact integers when stored as f32
+const ID_MASK: u32 = (1 << 24) - 1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aov {
+    Depth,
+    Normal,
+    Albedo,
+    ObjectId,
+    MaterialId,
+    DiffuseDirect,
+    DiffuseIndirect,
+    Specular,
+    Emission,
+    SampleCount,
+}
+
+impl Aov {
+    pub fn all() -> [Aov; 10] {
+        [Aov::Depth, Aov::Normal, Aov::Albedo, Aov::ObjectId, Aov::MaterialId, Aov::DiffuseDirect, Aov::DiffuseIndirect, Aov::Specular, Aov::Emission, Aov::SampleCount]
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::ObjectId => "object_id",
+            Aov::MaterialId => "material_id",
+            Aov::DiffuseDirect => "diffuse_direct",
+            Aov::DiffuseIndirect => "diffuse_indirect",
+            Aov::Specular => "specular",
+            Aov::Emission => "emission",
+            Aov::SampleCount => "sample_count"
+        }
+    }
+
+    pub fn get_channel_names(&self) -> &'static [&'static str] {
+        match self {
+            Aov::Depth => &["Z"],
+            Aov::Normal => &["X", "Y", "Z"],
+            Aov::ObjectId | Aov::MaterialId | Aov::SampleCount => &["V"],
+            _ => &["R", "G", "B"]
+        }
+    }
+
+    pub fn get_channels(&self) -> usize {
+        self.get_channel_names().len()
+    }
+
+    // lighting passes are on 0..255 scale of beauty image
+    pub fn is_radiance(&self) -> bool {
+        matches!(self, Aov::DiffuseDirect | Aov::DiffuseIndirect | Aov::Specular | Aov::Emission)
+    }
+
+    // ids would turn into meaningless values on object edges if they were averaged
+    fn is_averaged(&self) -> bool {
+        !matches!(self, Aov::ObjectId | Aov::MaterialId | Aov::SampleCount)
+    }
+}
+
+// values of one camera sample, lighting passes split first hit shading so that they sum up to beauty
+#[derive(Clone, Copy, Debug)]
+pub struct AovSample {
+    pub depth: f32,
+    pub normal: Vector3<f32>,
+    pub albedo: Color,
+    pub object_id: f32,
+    pub material_id: f32,
+    pub diffuse_direct: Color,
+    pub diffuse_indirect: Color,
+    pub specular: Color,
+    pub emission: Color,
+}
+
+impl AovSample {
+    pub fn new() -> Self {
+        AovSample {
+            depth: 0.,
+            normal: Vector3::new(0., 0., 0.),
+            albedo: Color::new(0., 0., 0.),
+            object_id: 0.,
+            material_id: 0.,
+            diffuse_direct: Color::new(0., 0., 0.),
+            diffuse_indirect: Color::new(0., 0., 0.),
+            specular: Color::new(0., 0., 0.),
+            emission: Color::new(0., 0., 0.)
+        }
+    }
+
+    fn get_values(&self, aov: Aov, sample_count: f32) -> [f32; 3] {
+        let color = |color: &Color| [color.r, color.g, color.b];
+
+        match aov {
+            Aov::Depth => [self.depth, 0., 0.],
+            Aov::Normal => [self.normal.x, self.normal.y, self.normal.z],
+            Aov::Albedo => color(&self.albedo),
+            Aov::ObjectId => [self.object_id, 0., 0.],
+            Aov::MaterialId => [self.material_id, 0., 0.],
+            Aov::DiffuseDirect => color(&self.diffuse_direct),
+            Aov::DiffuseIndirect => color(&self.diffuse_indirect),
+            Aov::Specular => color(&self.specular),
+            Aov::Emission => color(&self.emission),
+            Aov::SampleCount => [sample_count, 0., 0.]
+        }
+    }
+}
+
+impl Default for AovSample {
+    fn default() -> Self {
+        AovSample::new()
+    }
+}
+
+// materials with equal appearance share id no matter which renderable they belong to
+pub fn get_material_id(material: &Material) -> f32 {
+    let mut hash: u32 = 2166136261;
+    let mut add = |value: u32| {
+        for byte in value.to_le_bytes().iter() {
+            hash = (hash ^ *byte as u32).wrapping_mul(16777619);
+        }
+    };
+
+    for color in [material.diffuse_color, material.specular_color, material.emissive_color, material.transmission_color].iter() {
+        add(color.r.to_bits());
+        add(color.g.to_bits());
+        add(color.b.to_bits());
+    }
+
+    for value in [material.opacity, material.reflectivity, material.refractive_index, material.metallic, material.roughness].iter() {
+        add(value.to_bits());
+    }
+
+    if let Some(texture) = &material.diffuse_texture {
+        add(Arc::as_ptr(texture) as usize as u32);
+    }
+
+    // zero is left for background
+    u32::max(hash & ID_MASK, 1) as f32
+}
+
+// per pixel buffers of requested passes accumulated over frames like beauty image
+pub struct AovBuffers {
+    aovs: Vec<Aov>,
+    buffers: Vec<Vec<f32>>,
+}
+
+impl AovBuffers {
+    pub fn new(aovs: &[Aov], width: u32, height: u32) -> Self {
+        let pixels = (width * height) as usize;
+
+        AovBuffers {
+            aovs: aovs.to_vec(),
+            buffers: aovs.iter().map(|aov| vec![0.; pixels * aov.get_channels()]).collect()
+        }
+    }
+
+    pub fn get_aovs(&self) -> &Vec<Aov> {
+        &self.aovs
+    }
+
+    pub fn get(&self, aov: Aov) -> Option<&Vec<f32>> {
+        self.aovs.iter().position(|item| *item == aov).map(|index| &self.buffers[index])
+    }
+
+    pub fn clear(&mut self) {
+        for buffer in &mut self.buffers {
+            for value in buffer.iter_mut() { *value = 0.; }
+        }
+    }
+
+    // frames is count of samples pixel already holds
+    pub fn add_sample(&mut self, pixel: usize, sample: &AovSample, frames: u32) {
+        let frames = frames as f32;
+
+        for (aov, buffer) in self.aovs.iter().zip(self.buffers.iter_mut()) {
+            let channels = aov.get_channels();
+            let values = sample.get_values(*aov, frames + 1.);
+
+            for c in 0..channels {
+                let value = &mut buffer[pixel * channels + c];
+
+                *value = if aov.is_averaged() { (*value * frames + values[c]) / (frames + 1.) } else { values[c] };
+            }
+        }
+    }
+}
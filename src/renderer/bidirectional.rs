@@ -0,0 +1,421 @@
+use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+use crate::renderer::scene::Scene;
+use crate::renderer::light::{Light, LightKind};
+use crate::renderer::integrator::Integrator;
+use crate::renderer::bsdf::Bsdf;
+use crate::renderer::sampler::Sampler;
+use std::f32::consts::PI;
+
+const EPS: f32 = 0.0001;
+
+const CAMERA_STREAM: usize = 0;
+const LIGHT_STREAM: usize = 1;
+const CONNECTION_STREAM: usize = 2;
+
+#[derive(Clone, Copy, PartialEq)]
+enum VertexKind {
+    Camera,
+    Light,
+    Surface,
+}
+
+// pdfs are stored in area measure, forward in direction the subpath was generated
+struct Vertex {
+    kind: VertexKind,
+    point: Vector3<f32>,
+    normal: Option<Vector3<f32>>,
+    wo: Vector3<f32>,
+    bsdf: Option<Bsdf>,
+    emission: Color,
+    throughput: Color,
+    pdf_forward: f32,
+    pdf_reverse: f32,
+    delta: bool,
+}
+
+impl Vertex {
+    fn new(kind: VertexKind, point: Vector3<f32>, throughput: Color, pdf_forward: f32) -> Self {
+        Vertex {
+            kind,
+            point,
+            normal: None,
+            wo: Vector3::new(0., 0., 0.),
+            bsdf: None,
+            emission: Color::new(0., 0., 0.),
+            throughput,
+            pdf_forward,
+            pdf_reverse: 0.,
+            delta: false
+        }
+    }
+
+    fn is_connectible(&self) -> bool {
+        match &self.bsdf {
+            Some(bsdf) => !bsdf.is_delta(),
+            None => self.kind == VertexKind::Light
+        }
+    }
+
+    fn direction_to(&self, other: &Vertex) -> Vector3<f32> {
+        let mut direction = &other.point - &self.point;
+        direction.normalize();
+
+        direction
+    }
+
+    fn evaluate(&self, next: &Vertex) -> Color {
+        match &self.bsdf {
+            Some(bsdf) => bsdf.evaluate(&self.wo, &self.direction_to(next)),
+            None => Color::new(0., 0., 0.)
+        }
+    }
+
+    // solid angle density converted to area density at next vertex
+    fn convert_density(&self, pdf: f32, next: &Vertex) -> f32 {
+        let offset = &next.point - &self.point;
+        let distance_squared = offset.dot(&offset);
+
+        if distance_squared == 0. {
+            return 0.;
+        }
+
+        match &next.normal {
+            Some(normal) => pdf * f32::abs(normal.dot(&offset)) / (distance_squared * f32::sqrt(distance_squared)),
+            None => pdf / distance_squared
+        }
+    }
+
+    // area density of sampling next when arriving from previous
+    fn pdf(&self, previous: Option<&Vertex>, next: &Vertex) -> f32 {
+        let pdf = match (self.kind, &self.bsdf, previous) {
+            (VertexKind::Light, _, _) => 1. / (4. * PI),
+            (VertexKind::Surface, Some(bsdf), Some(previous)) => bsdf.pdf(&self.direction_to(previous), &self.direction_to(next)),
+            _ => 0.
+        };
+
+        self.convert_density(pdf, next)
+    }
+}
+
+// bidirectional path tracer connecting camera and light subpaths, every strategy is weighted
+// with balance heuristic. Light subpaths start at point lights, directional lights and emissive
+// surfaces are only reached by next event estimation and camera paths. Participating media and
+// subsurface scattering are not handled, media boundaries are treated as regular surfaces.
+pub struct BidirectionalIntegrator {
+    max_depth: usize,
+}
+
+impl BidirectionalIntegrator {
+    pub fn new() -> Self {
+        BidirectionalIntegrator {
+            max_depth: 5
+        }
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    fn get_point_lights(scene: &Scene) -> Vec<&Light> {
+        scene.get_lights().iter().filter(|light| matches!(light.kind, LightKind::Point)).collect()
+    }
+
+    fn get_emitted(light: &Light) -> Color {
+        light.color * light.intensity
+    }
+
+    fn offset_point(point: &Vector3<f32>, normal: &Option<Vector3<f32>>, direction: &Vector3<f32>) -> Vector3<f32> {
+        match normal {
+            Some(normal) if direction.dot(normal) < 0. => point - &(*normal * EPS),
+            Some(normal) => point + &(*normal * EPS),
+            None => *point
+        }
+    }
+
+    fn is_visible(scene: &Scene, from: &Vertex, to: &Vertex, time: f32) -> bool {
+        let direction = from.direction_to(to);
+        let origin = BidirectionalIntegrator::offset_point(&from.point, &from.normal, &direction);
+        let target = BidirectionalIntegrator::offset_point(&to.point, &to.normal, &-direction);
+        let distance = (&target - &origin).magnitude();
+
+        match scene.intersect(&Ray::new_with_time(origin, direction, time)) {
+            Some((intersection_data, _)) => intersection_data.distance >= distance - EPS,
+            None => true
+        }
+    }
+
+    fn is_black(color: &Color) -> bool {
+        color.r <= 0. && color.g <= 0. && color.b <= 0.
+    }
+
+    // extends subpath until max_vertices, returns background radiance when path escapes
+    fn random_walk(scene: &Scene, ray: Ray, throughput: Color, pdf: f32, max_vertices: usize, vertices: &mut Vec<Vertex>, sampler: &mut dyn Sampler) -> Color {
+        let mut ray = ray;
+        let mut throughput = throughput;
+        let mut pdf_forward = pdf;
+
+        while vertices.len() < max_vertices {
+            let (intersection_data, renderable) = match scene.intersect(&ray) {
+                Some(intersection) => intersection,
+                None => return throughput * scene.get_background_color(&ray.direction)
+            };
+
+            let material = renderable.get_material();
+            let point = &ray.origin + &(ray.direction * intersection_data.distance);
+
+            let mut vertex = Vertex::new(VertexKind::Surface, point, throughput, 0.);
+            vertex.normal = Some(intersection_data.normal);
+            vertex.wo = -ray.direction;
+            vertex.bsdf = Some(Bsdf::new(material, &intersection_data));
+            vertex.emission = material.emissive_color;
+            vertex.pdf_forward = vertices.last().unwrap().convert_density(pdf_forward, &vertex);
+
+            if vertices.len() + 1 == max_vertices {
+                vertices.push(vertex);
+                break;
+            }
+
+            let bsdf = vertex.bsdf.as_ref().unwrap();
+            let sample = match bsdf.sample(&vertex.wo, sampler.next_1d(), sampler.next_1d(), sampler.next_1d()) {
+                Some(sample) if sample.delta || sample.pdf > 0. => sample,
+                _ => {
+                    vertices.push(vertex);
+                    break;
+                }
+            };
+
+            let pdf_reverse = if sample.delta { 0. } else { bsdf.pdf(&sample.direction, &vertex.wo) };
+
+            let previous = vertices.last_mut().unwrap();
+            previous.pdf_reverse = vertex.convert_density(pdf_reverse, previous);
+
+            throughput = throughput * sample.weight;
+            pdf_forward = if sample.delta { 0. } else { sample.pdf };
+            vertex.delta = sample.delta;
+
+            let origin = BidirectionalIntegrator::offset_point(&point, &vertex.normal, &sample.direction);
+            ray = Ray::new_with_time(origin, sample.direction, ray.time);
+            vertices.push(vertex);
+
+            if BidirectionalIntegrator::is_black(&throughput) {
+                break;
+            }
+        }
+
+        Color::new(0., 0., 0.)
+    }
+
+    fn generate_light_subpath(&self, scene: &Scene, time: f32, sampler: &mut dyn Sampler) -> Vec<Vertex> {
+        let lights = BidirectionalIntegrator::get_point_lights(scene);
+        let mut vertices = Vec::new();
+
+        if lights.is_empty() {
+            return vertices;
+        }
+
+        let index = usize::min((sampler.next_1d() * lights.len() as f32) as usize, lights.len() - 1);
+        let light = lights[index];
+        let light_pdf = 1. / lights.len() as f32;
+        let emitted = BidirectionalIntegrator::get_emitted(light) / light_pdf;
+
+        vertices.push(Vertex::new(VertexKind::Light, light.position, emitted, light_pdf));
+
+        // uniform emission over sphere
+        let z = 1. - 2. * sampler.next_1d();
+        let radius = f32::sqrt(f32::max(0., 1. - z * z));
+        let phi = 2. * PI * sampler.next_1d();
+        let direction = Vector3::new(radius * f32::cos(phi), radius * f32::sin(phi), z);
+        let direction_pdf = 1. / (4. * PI);
+
+        let ray = Ray::new_with_time(light.position, direction, time);
+        BidirectionalIntegrator::random_walk(scene, ray, emitted / direction_pdf, direction_pdf, self.max_depth + 1, &mut vertices, sampler);
+
+        vertices
+    }
+
+    // camera vertex pdf is never used since strategies with single camera vertex are skipped
+    fn generate_camera_subpath(&self, scene: &Scene, ray: Ray, sampler: &mut dyn Sampler) -> (Vec<Vertex>, Color) {
+        let mut vertices = vec![Vertex::new(VertexKind::Camera, ray.origin, Color::new(1., 1., 1.), 1.)];
+        let background = BidirectionalIntegrator::random_walk(scene, ray, Color::new(1., 1., 1.), 1., self.max_depth + 2, &mut vertices, sampler);
+
+        (vertices, background)
+    }
+
+    fn remap(value: f32) -> f32 {
+        if value != 0. { value } else { 1. }
+    }
+
+    fn get_mis_weight(camera_vertices: &[Vertex], light_vertices: &[Vertex], sampled: Option<&Vertex>, s: usize, t: usize) -> f32 {
+        let mut camera: Vec<(f32, f32, bool)> = camera_vertices[..t].iter().map(|vertex| (vertex.pdf_forward, vertex.pdf_reverse, vertex.delta)).collect();
+        let mut light: Vec<(f32, f32, bool)> = light_vertices[..s].iter().map(|vertex| (vertex.pdf_forward, vertex.pdf_reverse, vertex.delta)).collect();
+
+        let qs = sampled.unwrap_or(&light_vertices[s - 1]);
+        let qs_minus = if s > 1 { Some(&light_vertices[s - 2]) } else { None };
+        let pt = &camera_vertices[t - 1];
+        let pt_minus = &camera_vertices[t - 2];
+
+        if let Some(sampled) = sampled {
+            light[s - 1] = (sampled.pdf_forward, sampled.pdf_reverse, sampled.delta);
+        }
+
+        camera[t - 1] = (camera[t - 1].0, qs.pdf(qs_minus, pt), false);
+        camera[t - 2].1 = pt.pdf(Some(qs), pt_minus);
+        light[s - 1] = (light[s - 1].0, pt.pdf(Some(pt_minus), qs), false);
+
+        if let Some(qs_minus) = qs_minus {
+            light[s - 2].1 = qs.pdf(Some(pt), qs_minus);
+        }
+
+        let mut sum = 0.;
+
+        let mut ratio = 1.;
+        for i in (2..t).rev() {
+            ratio *= BidirectionalIntegrator::remap(camera[i].1) / BidirectionalIntegrator::remap(camera[i].0);
+
+            if !camera[i].2 && !camera[i - 1].2 {
+                sum += ratio;
+            }
+        }
+
+        // light subpaths always start on delta point lights
+        let mut ratio = 1.;
+        for i in (0..s).rev() {
+            ratio *= BidirectionalIntegrator::remap(light[i].1) / BidirectionalIntegrator::remap(light[i].0);
+
+            let delta_light_vertex = if i > 0 { light[i - 1].2 } else { true };
+
+            if !light[i].2 && !delta_light_vertex {
+                sum += ratio;
+            }
+        }
+
+        1. / (1. + sum)
+    }
+
+    fn connect(scene: &Scene, camera_vertices: &[Vertex], light_vertices: &[Vertex], s: usize, t: usize, time: f32, sampler: &mut dyn Sampler) -> Color {
+        let black = Color::new(0., 0., 0.);
+        let pt = &camera_vertices[t - 1];
+
+        if s == 0 {
+            // only camera paths can reach emissive surfaces
+            return if pt.kind == VertexKind::Surface { pt.throughput * pt.emission } else { black };
+        }
+
+        if !pt.is_connectible() {
+            return black;
+        }
+
+        if s == 1 {
+            let lights = BidirectionalIntegrator::get_point_lights(scene);
+
+            if lights.is_empty() {
+                return black;
+            }
+
+            let index = usize::min((sampler.next_1d() * lights.len() as f32) as usize, lights.len() - 1);
+            let light_pdf = 1. / lights.len() as f32;
+            let sampled = Vertex::new(VertexKind::Light, lights[index].position, BidirectionalIntegrator::get_emitted(lights[index]) / light_pdf, light_pdf);
+
+            let offset = &sampled.point - &pt.point;
+            let cosine = f32::abs(pt.normal.unwrap().dot(&pt.direction_to(&sampled)));
+            let contribution = pt.throughput * pt.evaluate(&sampled) * sampled.throughput * (cosine / offset.dot(&offset));
+
+            if BidirectionalIntegrator::is_black(&contribution) || !BidirectionalIntegrator::is_visible(scene, pt, &sampled, time) {
+                return black;
+            }
+
+            return contribution * BidirectionalIntegrator::get_mis_weight(camera_vertices, light_vertices, Some(&sampled), s, t);
+        }
+
+        let qs = &light_vertices[s - 1];
+
+        if !qs.is_connectible() {
+            return black;
+        }
+
+        let offset = &qs.point - &pt.point;
+        let distance_squared = offset.dot(&offset);
+        let direction = pt.direction_to(qs);
+        let geometry = f32::abs(pt.normal.unwrap().dot(&direction)) * f32::abs(qs.normal.unwrap().dot(&direction)) / distance_squared;
+
+        let contribution = qs.throughput * qs.evaluate(pt) * pt.evaluate(qs) * pt.throughput * geometry;
+
+        if BidirectionalIntegrator::is_black(&contribution) || !BidirectionalIntegrator::is_visible(scene, pt, qs, time) {
+            return black;
+        }
+
+        contribution * BidirectionalIntegrator::get_mis_weight(camera_vertices, light_vertices, None, s, t)
+    }
+
+    // directional lights can't start light subpaths so they are sampled at every camera vertex
+    fn get_directional_light(scene: &Scene, vertex: &Vertex, time: f32, sampler: &mut dyn Sampler) -> Color {
+        let mut radiance = Color::new(0., 0., 0.);
+
+        if !vertex.is_connectible() {
+            return radiance;
+        }
+
+        for light in scene.get_lights() {
+            if let LightKind::Point = light.kind {
+                continue;
+            }
+
+            let (direction, _) = light.sample_direction(&vertex.point, sampler.next_1d(), sampler.next_1d());
+            let cosine = f32::abs(vertex.normal.unwrap().dot(&direction));
+            let contribution = vertex.throughput * vertex.bsdf.as_ref().unwrap().evaluate(&vertex.wo, &direction) * BidirectionalIntegrator::get_emitted(light) * cosine;
+
+            if BidirectionalIntegrator::is_black(&contribution) {
+                continue;
+            }
+
+            let origin = BidirectionalIntegrator::offset_point(&vertex.point, &vertex.normal, &direction);
+
+            if scene.intersect(&Ray::new_with_time(origin, direction, time)).is_none() {
+                radiance += contribution;
+            }
+        }
+
+        radiance
+    }
+}
+
+impl Default for BidirectionalIntegrator {
+    fn default() -> Self {
+        BidirectionalIntegrator::new()
+    }
+}
+
+impl Integrator for BidirectionalIntegrator {
+    fn get_radiance(&self, ray: Ray, scene: &Scene, sampler: &mut dyn Sampler) -> Color {
+        let time = ray.time;
+        sampler.start_stream(CAMERA_STREAM);
+        let (camera_vertices, background) = self.generate_camera_subpath(scene, ray, sampler);
+        sampler.start_stream(LIGHT_STREAM);
+        let light_vertices = self.generate_light_subpath(scene, time, sampler);
+        sampler.start_stream(CONNECTION_STREAM);
+
+        let mut radiance = background;
+
+        for t in 2..=camera_vertices.len() {
+            if t <= self.max_depth + 1 {
+                radiance += BidirectionalIntegrator::get_directional_light(scene, &camera_vertices[t - 1], time, sampler);
+            }
+
+            for s in 0..=light_vertices.len() {
+                if s + t > self.max_depth + 2 {
+                    break;
+                }
+
+                radiance += BidirectionalIntegrator::connect(scene, &camera_vertices, &light_vertices, s, t, time, sampler);
+            }
+        }
+
+        radiance
+    }
+}
@@ -0,0 +1,141 @@
+use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+use crate::math::frame::Frame;
+use crate::renderables::material::Material;
+use crate::renderables::renderable::IntersectionData;
+use std::f32::consts::PI;
+
+pub struct BsdfSample {
+    pub direction: Vector3<f32>,
+    pub weight: Color,
+    pub pdf: f32,
+    pub delta: bool,
+}
+
+// lambertian lobe mixed with perfect mirror and perfect refraction in the same
+// proportions the path tracer blends diffuse, reflected and transmitted light
+pub struct Bsdf {
+    normal: Vector3<f32>,
+    albedo: Color,
+    specular: Color,
+    transmission: Color,
+    refractive_index: f32,
+    diffuse_weight: f32,
+    reflection_weight: f32,
+}
+
+impl Bsdf {
+    pub fn new(material: &Material, intersection_data: &IntersectionData) -> Self {
+        let mut albedo = material.get_diffuse_color(intersection_data.uv) / 255.;
+
+        if let Some(vertex_color) = intersection_data.vertex_color {
+            albedo = albedo * (vertex_color / 255.);
+        }
+
+        let reflectivity = material.reflectivity.clamp(0., 1.);
+        let opacity = material.opacity.clamp(0., 1.);
+
+        Bsdf {
+            normal: intersection_data.normal,
+            albedo,
+            specular: material.specular_color / 255.,
+            transmission: material.get_transmission_tint(),
+            refractive_index: material.refractive_index,
+            diffuse_weight: (1. - reflectivity) * opacity,
+            reflection_weight: reflectivity,
+        }
+    }
+
+    pub fn get_normal(&self) -> &Vector3<f32> {
+        &self.normal
+    }
+
+    pub fn get_diffuse_weight(&self) -> f32 {
+        self.diffuse_weight
+    }
+
+    pub fn is_delta(&self) -> bool {
+        self.diffuse_weight <= 0.
+    }
+
+    // directions point away from surface
+    pub fn evaluate(&self, wo: &Vector3<f32>, wi: &Vector3<f32>) -> Color {
+        if wo.dot(&self.normal) * wi.dot(&self.normal) <= 0. {
+            return Color::new(0., 0., 0.);
+        }
+
+        self.albedo * (self.diffuse_weight / PI)
+    }
+
+    pub fn pdf(&self, wo: &Vector3<f32>, wi: &Vector3<f32>) -> f32 {
+        let cos_wi = wi.dot(&self.normal);
+
+        if wo.dot(&self.normal) * cos_wi <= 0. {
+            return 0.;
+        }
+
+        self.diffuse_weight * cos_wi.abs() / PI
+    }
+
+    // weight is bsdf times cosine divided by pdf
+    pub fn sample(&self, wo: &Vector3<f32>, r0: f32, r1: f32, r2: f32) -> Option<BsdfSample> {
+        let cos_wo = wo.dot(&self.normal);
+        let facing_normal = if cos_wo >= 0. { self.normal } else { -self.normal };
+
+        if r0 < self.diffuse_weight {
+            let radius = f32::sqrt(r1);
+            let phi = 2. * PI * r2;
+            let local = Vector3::new(radius * f32::cos(phi), radius * f32::sin(phi), f32::sqrt(f32::max(0., 1. - r1)));
+            let direction = Frame::new(Vector3::new(0., 0., 0.), facing_normal).to_world_direction(&local);
+
+            return Some(BsdfSample {
+                pdf: self.pdf(wo, &direction),
+                direction,
+                weight: self.albedo,
+                delta: false
+            });
+        }
+
+        if r0 < self.diffuse_weight + self.reflection_weight {
+            return Some(BsdfSample {
+                direction: Bsdf::reflect(wo, &facing_normal),
+                weight: self.specular,
+                pdf: self.reflection_weight,
+                delta: true
+            });
+        }
+
+        if self.diffuse_weight + self.reflection_weight >= 1. {
+            return None;
+        }
+
+        let direction = Bsdf::refract(wo, &facing_normal, if cos_wo >= 0. { 1. / self.refractive_index } else { self.refractive_index })
+            .unwrap_or_else(|| Bsdf::reflect(wo, &facing_normal));
+
+        Some(BsdfSample {
+            direction,
+            weight: self.transmission,
+            pdf: 1. - self.diffuse_weight - self.reflection_weight,
+            delta: true
+        })
+    }
+
+    fn reflect(wo: &Vector3<f32>, normal: &Vector3<f32>) -> Vector3<f32> {
+        &(*normal * (2. * wo.dot(normal))) - wo
+    }
+
+    // normal faces wo, None on total internal reflection
+    fn refract(wo: &Vector3<f32>, normal: &Vector3<f32>, eta: f32) -> Option<Vector3<f32>> {
+        let cos_incident = wo.dot(normal);
+        let k = 1. - eta * eta * (1. - cos_incident * cos_incident);
+
+        if k < 0. {
+            return None;
+        }
+
+        let mut direction = &(-*wo * eta) + &(*normal * (eta * cos_incident - f32::sqrt(k)));
+        direction.normalize();
+
+        Some(direction)
+    }
+}
@@ -1,13 +1,22 @@
 use crate::math::vec3::Vector3;
 use crate::math::ray::Ray;
 use crate::math::mat4::Matrix4;
+use crate::math::animation::Track;
+use crate::math::lerp;
 
+#[derive(Clone, Debug)]
 pub struct Camera {
-    pub position: Vector3<f32>,
-    pub target: Vector3<f32>,
+    position: Vector3<f32>,
+    target: Vector3<f32>,
     up: Vector3<f32>,
     fov: f32,
     camera_world: Matrix4<f32>,
+    shutter_open: f32,
+    shutter_close: f32,
+    position_track: Option<Track<Vector3<f32>>>,
+    target_track: Option<Track<Vector3<f32>>>,
+    // incremented by every method changing rays
+    version: u32,
 }
 
 impl Camera {
@@ -23,28 +32,145 @@ impl Camera {
             target,
             up,
             camera_world,
+            shutter_open: 0.,
+            shutter_close: 0.,
+            position_track: None,
+            target_track: None,
+            version: 0,
         }
     }
 
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    fn mark_changed(&mut self) {
+        self.version = self.version.wrapping_add(1);
+    }
+
     pub fn update(&mut self, new_position: &Vector3<f32>) {
         self.position = &self.target + &new_position;
         self.camera_world.look_at(&self.position, &self.target, &self.up);
+        self.mark_changed();
+    }
+
+    pub fn apply_matrix(&mut self, matrix: &Matrix4<f32>) {
+        self.position.apply_matrix(matrix);
+        self.target.apply_matrix(matrix);
+        self.up.transform_direction(matrix);
+        self.camera_world.look_at(&self.position, &self.target, &self.up);
+        self.mark_changed();
+    }
+
+    pub fn get_fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+        self.mark_changed();
+    }
+
+    pub fn get_up(&self) -> Vector3<f32> {
+        self.up
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vector3<f32>) {
+        self.position = position;
+        self.camera_world.look_at(&self.position, &self.target, &self.up);
+        self.mark_changed();
+    }
+
+    pub fn get_target(&self) -> Vector3<f32> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Vector3<f32>) {
+        self.target = target;
+        self.camera_world.look_at(&self.position, &self.target, &self.up);
+        self.mark_changed();
+    }
+
+    // rays are spread uniformly over time between shutter open and close
+    pub fn set_shutter(&mut self, open: f32, close: f32) {
+        self.shutter_open = open;
+        self.shutter_close = close;
+        self.mark_changed();
+    }
+
+    pub fn get_shutter(&self) -> (f32, f32) {
+        (self.shutter_open, self.shutter_close)
+    }
+
+    pub fn set_position_track(&mut self, track: Track<Vector3<f32>>) {
+        self.position_track = Some(track);
+        self.mark_changed();
+    }
+
+    pub fn set_target_track(&mut self, track: Track<Vector3<f32>>) {
+        self.target_track = Some(track);
+        self.mark_changed();
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.position_track.is_some() || self.target_track.is_some()
+    }
+
+    pub fn get_position_at(&self, time: f32) -> Vector3<f32> {
+        self.position_track.as_ref().and_then(|track| track.sample(time)).unwrap_or(self.position)
+    }
+
+    pub fn get_target_at(&self, time: f32) -> Vector3<f32> {
+        self.target_track.as_ref().and_then(|track| track.sample(time)).unwrap_or(self.target)
+    }
+
+    // moves camera to its animated pose, shutter is left untouched
+    pub fn set_time(&mut self, time: f32) {
+        if self.is_animated() {
+            self.position = self.get_position_at(time);
+            self.target = self.get_target_at(time);
+            self.camera_world.look_at(&self.position, &self.target, &self.up);
+            self.mark_changed();
+        }
+    }
+
+    // maps random number in [0, 1) to time inside of shutter interval
+    pub fn get_shutter_time(&self, sample: f32) -> f32 {
+        if self.shutter_close > self.shutter_open {
+            lerp(self.shutter_open, self.shutter_close, sample)
+        } else {
+            self.shutter_open
+        }
     }
 
     pub fn get_camera_ray(&self, x: u32, y: u32, width: u32, height: u32) -> Ray {
+        self.get_camera_ray_at(x as f32 + 0.5, y as f32 + 0.5, width, height, self.get_shutter_time(rand::random()))
+    }
+
+    // continuous film position in pixels
+    pub fn get_camera_ray_at(&self, x: f32, y: f32, width: u32, height: u32, time: f32) -> Ray {
         let fov_adjustment = (self.fov.to_radians() / 2.0).tan();
         let aspect_ratio = (width as f32) / (height as f32);
-        let dir_x = ((((x as f32 + 0.5) / width as f32) * 2.0 - 1.0) * aspect_ratio) * fov_adjustment;
-        let dir_y = (1.0 - ((y as f32 + 0.5) / height as f32) * 2.0) * fov_adjustment;
+        let dir_x = (((x / width as f32) * 2.0 - 1.0) * aspect_ratio) * fov_adjustment;
+        let dir_y = (1.0 - (y / height as f32) * 2.0) * fov_adjustment;
 
-        let mut inverse_camera = self.camera_world.clone();
-        inverse_camera.inverse();
+        let mut position = self.position;
+        let mut camera_world = self.camera_world;
+
+        if self.is_animated() {
+            position = self.get_position_at(time);
+            camera_world.look_at(&position, &self.get_target_at(time), &self.up);
+        }
 
         let mut direction = Vector3::new(dir_x, dir_y, -1.0);
 
-        direction.apply_matrix(&self.camera_world);
+        direction.apply_matrix(&camera_world);
         direction.normalize();
 
-        Ray::new(self.position, direction)
+        Ray::new_with_time(position, direction, time)
     }
 }
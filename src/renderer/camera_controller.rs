@@ -12,7 +12,7 @@ pub struct CameraController {
 
 impl CameraController  {
     pub fn new(cam: &Camera) -> Self {
-       let cam_position = &cam.position;
+       let cam_position = cam.get_position();
 
        CameraController {
            spherical: Spherical::from_cartesian(cam_position.x, cam_position.y, cam_position.z),
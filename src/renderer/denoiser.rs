@@ -0,0 +1,148 @@
+const KERNEL: [f32; 5] = [1. / 16., 1. / 4., 3. / 8., 1. / 4., 1. / 16.];
+const ALBEDO_EPS: f32 = 0.01;
+const EPS: f32 = 0.0001;
+
+// first hit features averaged over frames in the same way as beauty image, albedo is in 0..1 range
+pub struct GuideBuffers {
+    pub albedo: Vec<f32>,
+    pub normal: Vec<f32>,
+    pub depth: Vec<f32>,
+}
+
+impl GuideBuffers {
+    pub fn new(width: u32, height: u32) -> Self {
+        let pixels = (width * height) as usize;
+
+        GuideBuffers {
+            albedo: vec![0.; pixels * 3],
+            normal: vec![0.; pixels * 3],
+            depth: vec![0.; pixels]
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for value in self.albedo.iter_mut().chain(self.normal.iter_mut()).chain(self.depth.iter_mut()) {
+            *value = 0.;
+        }
+    }
+}
+
+// edge avoiding a-trous wavelet filter (Dammertz et al. 2010) applied to illumination with albedo
+// divided out, so that texture detail is kept. Color tolerance shrinks with accumulated frame count
+// as noise of progressive image falls.
+pub struct Denoiser {
+    iterations: usize,
+    color_sigma: f32,
+    normal_sigma: f32,
+    depth_sigma: f32,
+}
+
+impl Denoiser {
+    pub fn new() -> Self {
+        Denoiser {
+            iterations: 5,
+            color_sigma: 200.,
+            normal_sigma: 64.,
+            depth_sigma: 0.05
+        }
+    }
+
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    // tolerated difference of illumination with albedo divided out for single frame
+    pub fn set_color_sigma(&mut self, color_sigma: f32) {
+        self.color_sigma = color_sigma;
+    }
+
+    // exponent applied to cosine between normals
+    pub fn set_normal_sigma(&mut self, normal_sigma: f32) {
+        self.normal_sigma = normal_sigma;
+    }
+
+    // tolerated relative depth difference per pixel
+    pub fn set_depth_sigma(&mut self, depth_sigma: f32) {
+        self.depth_sigma = depth_sigma;
+    }
+
+    pub fn denoise(&self, image: &[f32], guides: &GuideBuffers, width: u32, height: u32, frames: u32) -> Vec<f32> {
+        let albedo = &guides.albedo;
+        let mut illumination: Vec<f32> = image.iter().zip(albedo.iter()).map(|(color, albedo)| color / (albedo + ALBEDO_EPS)).collect();
+        let mut filtered = vec![0.; illumination.len()];
+
+        let mut color_sigma = self.color_sigma / f32::sqrt(u32::max(frames, 1) as f32);
+
+        for iteration in 0..self.iterations {
+            self.filter_pass(&illumination, &mut filtered, guides, (width as i32, height as i32), 1 << iteration, color_sigma);
+            std::mem::swap(&mut illumination, &mut filtered);
+            color_sigma *= 0.5;
+        }
+
+        illumination.iter().zip(albedo.iter()).map(|(color, albedo)| color * (albedo + ALBEDO_EPS)).collect()
+    }
+
+    fn filter_pass(&self, input: &[f32], output: &mut [f32], guides: &GuideBuffers, size: (i32, i32), step: i32, color_sigma: f32) {
+        let (width, height) = size;
+        let color_factor = 1. / f32::max(color_sigma * color_sigma, EPS);
+
+        for y in 0..height {
+            for x in 0..width {
+                let p = (y * width + x) as usize;
+                let color_p = &input[p * 3..p * 3 + 3];
+                let normal_p = &guides.normal[p * 3..p * 3 + 3];
+                let depth_p = guides.depth[p];
+
+                let mut sum = [0.; 3];
+                let mut weight_sum = 0.;
+
+                for j in -2..=2 {
+                    for i in -2..=2 {
+                        let qx = x + i * step;
+                        let qy = y + j * step;
+
+                        if qx < 0 || qy < 0 || qx >= width || qy >= height {
+                            continue;
+                        }
+
+                        let q = (qy * width + qx) as usize;
+                        let color_q = &input[q * 3..q * 3 + 3];
+                        let normal_q = &guides.normal[q * 3..q * 3 + 3];
+                        let depth_q = guides.depth[q];
+
+                        let color_distance: f32 = (0..3).map(|c| (color_p[c] - color_q[c]) * (color_p[c] - color_q[c])).sum();
+                        let color_weight = f32::exp(-color_distance * color_factor);
+
+                        let cosine: f32 = (0..3).map(|c| normal_p[c] * normal_q[c]).sum();
+                        let normal_weight = f32::powf(f32::max(cosine, 0.), self.normal_sigma);
+
+                        let depth_scale = self.depth_sigma * f32::max(depth_p, depth_q) * (step * i32::max(i.abs(), j.abs())) as f32;
+                        let depth_weight = f32::exp(-(depth_p - depth_q).abs() / f32::max(depth_scale, EPS));
+
+                        let weight = KERNEL[(i + 2) as usize] * KERNEL[(j + 2) as usize] * color_weight * normal_weight * depth_weight;
+
+                        if !weight.is_finite() {
+                            continue;
+                        }
+
+                        for c in 0..3 {
+                            sum[c] += color_q[c] * weight;
+                        }
+
+                        weight_sum += weight;
+                    }
+                }
+
+                for c in 0..3 {
+                    output[p * 3 + c] = if weight_sum > 0. { sum[c] / weight_sum } else { color_p[c] };
+                }
+            }
+        }
+    }
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Denoiser::new()
+    }
+}
@@ -0,0 +1,60 @@
+use crate::renderer::scene::Scene;
+use crate::renderer::renderer::Renderer;
+use crate::renderer::camera::Camera;
+use crate::renderer::sampler::Sampler;
+use crate::renderer::aov::AovSample;
+use crate::math::ray::Ray;
+use crate::math::color::Color;
+
+pub trait Integrator: Send + Sync {
+    // called once per frame before any radiance is requested
+    fn prepare(&mut self, _scene: &Scene) {}
+
+    // called whenever accumulated image is discarded
+    fn reset(&mut self) {}
+
+    fn get_radiance(&self, ray: Ray, scene: &Scene, sampler: &mut dyn Sampler) -> Color;
+
+    // integrators placing samples anywhere on image return accumulated splats for share of
+    // sample_count pixels instead of being asked for radiance of every pixel
+    fn render_samples(&self, _camera: &Camera, _scene: &Scene, _width: u32, _height: u32, _sample_count: usize) -> Option<Vec<f32>> {
+        None
+    }
+
+    fn is_splatting(&self) -> bool {
+        false
+    }
+
+    // integrators that can't split their lighting into passes fill only geometric ones
+    fn get_radiance_with_aovs(&self, ray: Ray, scene: &Scene, sampler: &mut dyn Sampler, aovs: &mut AovSample) -> Color {
+        Renderer::record_geometry(&ray, scene, aovs);
+        self.get_radiance(ray, scene, sampler)
+    }
+}
+
+// unidirectional tracer with next event estimation towards scene lights, keeps light units of the original
+// tracer, so its images are not comparable with other integrators which use physical falloff and brdf
+pub struct PathIntegrator;
+
+impl PathIntegrator {
+    pub fn new() -> Self {
+        PathIntegrator
+    }
+}
+
+impl Default for PathIntegrator {
+    fn default() -> Self {
+        PathIntegrator::new()
+    }
+}
+
+impl Integrator for PathIntegrator {
+    // existing tracer draws its own random numbers
+    fn get_radiance(&self, ray: Ray, scene: &Scene, _sampler: &mut dyn Sampler) -> Color {
+        Renderer::trace(ray, scene, 0, scene.get_medium())
+    }
+
+    fn get_radiance_with_aovs(&self, ray: Ray, scene: &Scene, _sampler: &mut dyn Sampler, aovs: &mut AovSample) -> Color {
+        Renderer::trace_with_aovs(ray, scene, 0, scene.get_medium(), Some(aovs))
+    }
+}
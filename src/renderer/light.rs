@@ -1,11 +1,45 @@
 use crate::math::vec3::Vector3;
 use crate::math::color::Color;
+use crate::math::animation::Track;
+use std::f32::consts::PI;
+
+#[derive(Clone, Debug)]
+pub enum LightKind {
+    Point,
+    Directional { direction: Vector3<f32>, angular_radius: f32 }
+}
+
+// world space tracks, empty tracks leave the property untouched
+#[derive(Clone, Debug)]
+pub struct LightAnimation {
+    pub color: Track<Color>,
+    pub intensity: Track<f32>,
+    pub position: Track<Vector3<f32>>,
+}
+
+impl LightAnimation {
+    pub fn new() -> Self {
+        LightAnimation {
+            color: Track::new(),
+            intensity: Track::new(),
+            position: Track::new()
+        }
+    }
+}
+
+impl Default for LightAnimation {
+    fn default() -> Self {
+        LightAnimation::new()
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Light {
     pub color: Color,
     pub intensity: f32,
-    pub position: Vector3<f32>
+    pub position: Vector3<f32>,
+    pub kind: LightKind,
+    pub animation: Option<LightAnimation>
 }
 
 impl Light {
@@ -13,7 +47,92 @@ impl Light {
         Light {
             color,
             intensity,
-            position
+            position,
+            kind: LightKind::Point,
+            animation: None
+        }
+    }
+
+    pub fn new_directional(color: Color, intensity: f32, direction: Vector3<f32>, angular_radius: f32) -> Self {
+        let mut direction = direction;
+        direction.normalize();
+
+        Light {
+            color,
+            intensity,
+            position: Vector3::new(0., 0., 0.),
+            kind: LightKind::Directional { direction, angular_radius },
+            animation: None
+        }
+    }
+
+    pub fn set_animation(&mut self, animation: LightAnimation) {
+        self.animation = Some(animation);
+    }
+
+    pub fn set_time(&mut self, time: f32) {
+        if let Some(animation) = &self.animation {
+            if let Some(color) = animation.color.sample(time) {
+                self.color = color;
+            }
+
+            if let Some(intensity) = animation.intensity.sample(time) {
+                self.intensity = intensity;
+            }
+
+            if let Some(position) = animation.position.sample(time) {
+                self.position = position;
+            }
         }
     }
+
+    // light arriving at distance on 0..255 scale, point lights fall off with square of distance
+    pub fn get_incident_light(&self, distance: f32) -> Color {
+        match self.kind {
+            LightKind::Point => self.color * (self.intensity / (distance * distance)),
+            LightKind::Directional { .. } => self.color * self.intensity
+        }
+    }
+
+    // returns normalized direction from point towards the light and distance to it
+    pub fn sample_direction(&self, point: &Vector3<f32>, r1: f32, r2: f32) -> (Vector3<f32>, f32) {
+        match &self.kind {
+            LightKind::Point => {
+                let mut direction = &self.position - point;
+                let distance = direction.magnitude();
+                direction.normalize();
+
+                (direction, distance)
+            },
+            LightKind::Directional { direction, angular_radius } => {
+                (Light::sample_cone(direction, *angular_radius, r1, r2), f32::INFINITY)
+            }
+        }
+    }
+
+    fn sample_cone(axis: &Vector3<f32>, angular_radius: f32, r1: f32, r2: f32) -> Vector3<f32> {
+        if angular_radius <= 0. {
+            return *axis;
+        }
+
+        let cos_max = f32::cos(angular_radius);
+        let cos_theta = 1. - r1 * (1. - cos_max);
+        let sin_theta = f32::sqrt(f32::max(0., 1. - cos_theta * cos_theta));
+        let phi = r2 * 2. * PI;
+
+        let mut tangent = if axis.x.abs() > axis.y.abs() {
+            Vector3::new(axis.z, 0., -axis.x)
+        } else {
+            Vector3::new(0., -axis.z, axis.y)
+        };
+        tangent.normalize();
+
+        let mut bitangent = *axis;
+        bitangent.cross(&tangent);
+
+        let mut direction = &(&(tangent * (sin_theta * f32::cos(phi))) + &(bitangent * (sin_theta * f32::sin(phi)))) + &(*axis * cos_theta);
+        direction.normalize();
+
+        direction
+    }
 }
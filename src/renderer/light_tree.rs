@@ -0,0 +1,155 @@
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+use crate::renderer::light::{Light, LightKind};
+
+const MIN_DISTANCE_SQUARED: f32 = 0.0001;
+
+enum LightNodeKind {
+    Leaf(usize),
+    Inner(usize, usize),
+}
+
+struct LightNode {
+    bbox: BBox,
+    power: f32,
+    kind: LightNodeKind,
+}
+
+// bounding volume hierarchy over point lights, lights are picked by descending the tree with
+// probability proportional to power over distance to every child. Directional lights have no
+// position so they are kept aside and picked by their intensity.
+pub struct LightTree {
+    nodes: Vec<LightNode>,
+    root: Option<usize>,
+    directional: Vec<usize>,
+    directional_power: f32,
+}
+
+impl LightTree {
+    pub fn new(lights: &[Light]) -> Self {
+        let mut tree = LightTree {
+            nodes: Vec::new(),
+            root: None,
+            directional: Vec::new(),
+            directional_power: 0.
+        };
+
+        let mut point_lights = Vec::new();
+
+        for (index, light) in lights.iter().enumerate() {
+            match light.kind {
+                LightKind::Point => point_lights.push(index),
+                LightKind::Directional { .. } => {
+                    tree.directional.push(index);
+                    tree.directional_power += f32::max(light.intensity, 0.);
+                }
+            }
+        }
+
+        if !point_lights.is_empty() {
+            tree.root = Some(tree.build(lights, &mut point_lights));
+        }
+
+        tree
+    }
+
+    fn build(&mut self, lights: &[Light], indices: &mut [usize]) -> usize {
+        if indices.len() == 1 {
+            let light = &lights[indices[0]];
+
+            self.nodes.push(LightNode {
+                bbox: BBox::new(light.position, light.position),
+                power: f32::max(light.intensity, 0.),
+                kind: LightNodeKind::Leaf(indices[0])
+            });
+
+            return self.nodes.len() - 1;
+        }
+
+        let positions: Vec<Vector3<f32>> = indices.iter().map(|index| lights[*index].position).collect();
+        let size = BBox::new_from_points(&positions).get_size();
+        let axis = if size.x >= size.y && size.x >= size.z { 0 } else if size.y >= size.z { 1 } else { 2 };
+        let component = |index: &usize| match axis {
+            0 => lights[*index].position.x,
+            1 => lights[*index].position.y,
+            _ => lights[*index].position.z
+        };
+
+        let median = indices.len() / 2;
+        indices.select_nth_unstable_by(median, |a, b| component(a).partial_cmp(&component(b)).unwrap_or(std::cmp::Ordering::Equal));
+
+        let (left_indices, right_indices) = indices.split_at_mut(median);
+        let left = self.build(lights, left_indices);
+        let right = self.build(lights, right_indices);
+
+        self.nodes.push(LightNode {
+            bbox: self.nodes[left].bbox.union(&self.nodes[right].bbox),
+            power: self.nodes[left].power + self.nodes[right].power,
+            kind: LightNodeKind::Inner(left, right)
+        });
+
+        self.nodes.len() - 1
+    }
+
+    // distance is clamped by node extent so that point inside of cluster doesn't favor it infinitely
+    fn get_importance(&self, node: usize, point: &Vector3<f32>) -> f32 {
+        let node = &self.nodes[node];
+        let offset = &node.bbox.get_center() - point;
+        let radius = node.bbox.get_size().magnitude() * 0.5;
+
+        node.power / f32::max(f32::max(offset.dot(&offset), radius * radius), MIN_DISTANCE_SQUARED)
+    }
+
+    // returns light index with its probability
+    pub fn sample(&self, lights: &[Light], point: &Vector3<f32>, sample: f32) -> Option<(usize, f32)> {
+        let tree_importance = self.root.map_or(0., |root| self.get_importance(root, point));
+        let total = tree_importance + self.directional_power;
+
+        if total <= 0. {
+            return None;
+        }
+
+        let mut sample = sample * total;
+
+        if sample < self.directional_power {
+            for index in &self.directional {
+                let power = f32::max(lights[*index].intensity, 0.);
+
+                if sample < power {
+                    return Some((*index, power / total));
+                }
+
+                sample -= power;
+            }
+
+            let index = *self.directional.last().unwrap();
+            return Some((index, f32::max(lights[index].intensity, 0.) / total));
+        }
+
+        let mut sample = f32::min((sample - self.directional_power) / tree_importance, 1.);
+        let mut probability = tree_importance / total;
+        let mut node = self.root?;
+
+        loop {
+            match self.nodes[node].kind {
+                LightNodeKind::Leaf(index) => return Some((index, probability)),
+                LightNodeKind::Inner(left, right) => {
+                    let left_importance = self.get_importance(left, point);
+                    let right_importance = self.get_importance(right, point);
+                    let sum = left_importance + right_importance;
+                    let left_probability = if sum > 0. { left_importance / sum } else { 0.5 };
+
+                    if sample < left_probability {
+                        sample /= left_probability;
+                        probability *= left_probability;
+                        node = left;
+                    } else {
+                        sample = f32::min((sample - left_probability) / (1. - left_probability), 1.);
+                        probability *= 1. - left_probability;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+}
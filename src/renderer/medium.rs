@@ -0,0 +1,281 @@
+use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+use crate::math::bbox::BBox;
+use crate::math::lerp;
+use std::f32::consts::PI;
+
+const MAX_TRACKING_STEPS: usize = 1024;
+
+// density values on regular grid spanning bbox, zero outside of it
+#[derive(Clone, Debug)]
+pub struct DensityGrid {
+    bbox: BBox,
+    resolution: (usize, usize, usize),
+    data: Vec<f32>,
+    max_density: f32,
+}
+
+impl DensityGrid {
+    pub fn new(bbox: BBox, resolution: (usize, usize, usize), data: Vec<f32>) -> Self {
+        assert_eq!(data.len(), resolution.0 * resolution.1 * resolution.2);
+
+        let max_density = data.iter().fold(0., |max: f32, value| f32::max(max, *value));
+
+        DensityGrid {
+            bbox,
+            resolution,
+            data,
+            max_density
+        }
+    }
+
+    // fills grid by evaluating density at voxel centers
+    pub fn from_fn<F>(bbox: BBox, resolution: (usize, usize, usize), density: F) -> Self where F: Fn(&Vector3<f32>) -> f32 {
+        let size = bbox.get_size();
+        let mut data = Vec::with_capacity(resolution.0 * resolution.1 * resolution.2);
+
+        for z in 0..resolution.2 {
+            for y in 0..resolution.1 {
+                for x in 0..resolution.0 {
+                    let point = Vector3::new(
+                        bbox.min.x + size.x * (x as f32 + 0.5) / resolution.0 as f32,
+                        bbox.min.y + size.y * (y as f32 + 0.5) / resolution.1 as f32,
+                        bbox.min.z + size.z * (z as f32 + 0.5) / resolution.2 as f32
+                    );
+
+                    data.push(density(&point));
+                }
+            }
+        }
+
+        DensityGrid::new(bbox, resolution, data)
+    }
+
+    pub fn get_bbox(&self) -> &BBox {
+        &self.bbox
+    }
+
+    pub fn get_max_density(&self) -> f32 {
+        self.max_density
+    }
+
+    fn get_voxel(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.data[(z * self.resolution.1 + y) * self.resolution.0 + x]
+    }
+
+    // trilinear interpolation between voxel centers
+    pub fn sample(&self, point: &Vector3<f32>) -> f32 {
+        let (min, max) = (&self.bbox.min, &self.bbox.max);
+
+        if point.x < min.x || point.y < min.y || point.z < min.z || point.x > max.x || point.y > max.y || point.z > max.z {
+            return 0.;
+        }
+
+        let size = self.bbox.get_size();
+        let to_grid = |value: f32, min: f32, size: f32, resolution: usize| {
+            let position = f32::max((value - min) / size * resolution as f32 - 0.5, 0.);
+            let index = usize::min(position as usize, resolution - 1);
+
+            (index, usize::min(index + 1, resolution - 1), position - index as f32)
+        };
+
+        let (x0, x1, fx) = to_grid(point.x, min.x, size.x, self.resolution.0);
+        let (y0, y1, fy) = to_grid(point.y, min.y, size.y, self.resolution.1);
+        let (z0, z1, fz) = to_grid(point.z, min.z, size.z, self.resolution.2);
+        let fx = f32::min(fx, 1.);
+        let fy = f32::min(fy, 1.);
+        let fz = f32::min(fz, 1.);
+
+        let bottom = lerp(
+            lerp(self.get_voxel(x0, y0, z0), self.get_voxel(x1, y0, z0), fx),
+            lerp(self.get_voxel(x0, y1, z0), self.get_voxel(x1, y1, z0), fx),
+            fy
+        );
+        let top = lerp(
+            lerp(self.get_voxel(x0, y0, z1), self.get_voxel(x1, y0, z1), fx),
+            lerp(self.get_voxel(x0, y1, z1), self.get_voxel(x1, y1, z1), fx),
+            fy
+        );
+
+        lerp(bottom, top, fz)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Density {
+    Homogeneous,
+    Grid(DensityGrid),
+}
+
+// absorption and scattering coefficients are per unit length and scaled by density
+#[derive(Clone, Debug)]
+pub struct Medium {
+    pub absorption: f32,
+    pub scattering: f32,
+    pub color: Color,
+    pub anisotropy: f32,
+    pub density: Density,
+}
+
+impl Medium {
+    pub fn new_homogeneous(absorption: f32, scattering: f32, color: Color, anisotropy: f32) -> Self {
+        Medium {
+            absorption,
+            scattering,
+            color,
+            anisotropy,
+            density: Density::Homogeneous
+        }
+    }
+
+    pub fn new_heterogeneous(absorption: f32, scattering: f32, color: Color, anisotropy: f32, grid: DensityGrid) -> Self {
+        Medium {
+            absorption,
+            scattering,
+            color,
+            anisotropy,
+            density: Density::Grid(grid)
+        }
+    }
+
+    pub fn get_extinction(&self) -> f32 {
+        self.absorption + self.scattering
+    }
+
+    pub fn get_albedo(&self) -> f32 {
+        let extinction = self.get_extinction();
+
+        if extinction > 0. { self.scattering / extinction } else { 0. }
+    }
+
+    // heterogeneous media only have to be tracked where grid is
+    fn get_interval(&self, ray: &Ray, max_distance: f32) -> Option<(f32, f32)> {
+        match &self.density {
+            Density::Homogeneous => Some((0., max_distance)),
+            Density::Grid(grid) => grid.get_bbox().ray_interval(ray)
+                .map(|(near, far)| (f32::max(near, 0.), f32::min(far, max_distance)))
+                .filter(|(near, far)| near < far)
+        }
+    }
+
+    // distance to next real scattering or absorption event, None when ray leaves medium first
+    pub fn sample_distance(&self, ray: &Ray, max_distance: f32) -> Option<f32> {
+        let extinction = self.get_extinction();
+        let (start, end) = self.get_interval(ray, max_distance)?;
+
+        match &self.density {
+            Density::Homogeneous => {
+                if extinction <= 0. {
+                    return None;
+                }
+
+                let distance = -f32::ln(1. - rand::random::<f32>()) / extinction;
+
+                if distance < end { Some(distance) } else { None }
+            },
+            Density::Grid(grid) => {
+                // delta tracking against majorant of the grid
+                let majorant = extinction * grid.get_max_density();
+
+                if majorant <= 0. {
+                    return None;
+                }
+
+                let mut t = start;
+
+                for _ in 0..MAX_TRACKING_STEPS {
+                    t -= f32::ln(1. - rand::random::<f32>()) / majorant;
+
+                    if t >= end {
+                        return None;
+                    }
+
+                    let point = &ray.origin + &(ray.direction * t);
+
+                    if rand::random::<f32>() < extinction * grid.sample(&point) / majorant {
+                        return Some(t);
+                    }
+                }
+
+                None
+            }
+        }
+    }
+
+    pub fn get_transmittance(&self, ray: &Ray, max_distance: f32) -> f32 {
+        let extinction = self.get_extinction();
+
+        let (start, end) = match self.get_interval(ray, max_distance) {
+            Some(interval) => interval,
+            None => return 1.
+        };
+
+        match &self.density {
+            Density::Homogeneous => {
+                if end.is_finite() { f32::exp(-extinction * (end - start)) } else if extinction > 0. { 0. } else { 1. }
+            },
+            Density::Grid(grid) => {
+                // ratio tracking
+                let majorant = extinction * grid.get_max_density();
+
+                if majorant <= 0. {
+                    return 1.;
+                }
+
+                let mut t = start;
+                let mut transmittance = 1.;
+
+                for _ in 0..MAX_TRACKING_STEPS {
+                    t -= f32::ln(1. - rand::random::<f32>()) / majorant;
+
+                    if t >= end {
+                        break;
+                    }
+
+                    let point = &ray.origin + &(ray.direction * t);
+                    transmittance *= 1. - extinction * grid.sample(&point) / majorant;
+                }
+
+                transmittance
+            }
+        }
+    }
+
+    // Henyey-Greenstein phase function for angle between incoming direction and scattered one
+    pub fn get_phase(&self, cos_theta: f32) -> f32 {
+        let g = self.anisotropy;
+        let denominator = 1. + g * g - 2. * g * cos_theta;
+
+        (1. - g * g) / (4. * PI * denominator * f32::sqrt(denominator))
+    }
+
+    pub fn sample_phase(&self, direction: &Vector3<f32>, r1: f32, r2: f32) -> Vector3<f32> {
+        let g = self.anisotropy;
+
+        let cos_theta = if g.abs() < 0.001 {
+            1. - 2. * r1
+        } else {
+            let square = (1. - g * g) / (1. - g + 2. * g * r1);
+            (1. + g * g - square * square) / (2. * g)
+        };
+
+        let sin_theta = f32::sqrt(f32::max(0., 1. - cos_theta * cos_theta));
+        let phi = 2. * PI * r2;
+
+        let mut tangent = if direction.x.abs() > direction.y.abs() {
+            Vector3::new(direction.z, 0., -direction.x)
+        } else {
+            Vector3::new(0., -direction.z, direction.y)
+        };
+        tangent.normalize();
+
+        let mut bitangent = *direction;
+        bitangent.cross(&tangent);
+
+        let mut scattered = &(&(tangent * (sin_theta * f32::cos(phi))) + &(bitangent * (sin_theta * f32::sin(phi)))) + &(*direction * cos_theta);
+        scattered.normalize();
+
+        scattered
+    }
+}
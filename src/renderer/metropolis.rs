@@ -0,0 +1,161 @@
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+use crate::renderer::scene::Scene;
+use crate::renderer::camera::Camera;
+use crate::renderer::integrator::Integrator;
+use crate::renderer::bidirectional::BidirectionalIntegrator;
+use crate::renderer::sampler::{Sampler, MetropolisSampler};
+
+// bidirectional integrator uses first three streams
+const FILM_STREAM: usize = 3;
+const STREAM_COUNT: usize = 4;
+
+struct PathSample {
+    x: f32,
+    y: f32,
+    radiance: Color,
+    luminance: f32,
+}
+
+// primary sample space metropolis light transport on top of bidirectional path tracer, every worker
+// runs independent markov chain normalized by its own bootstrap estimate of image brightness
+pub struct MetropolisIntegrator {
+    bidirectional: BidirectionalIntegrator,
+    bootstrap_samples: usize,
+    mutations_per_pixel: usize,
+    sigma: f32,
+    large_step_probability: f32,
+}
+
+impl MetropolisIntegrator {
+    pub fn new() -> Self {
+        MetropolisIntegrator {
+            bidirectional: BidirectionalIntegrator::new(),
+            bootstrap_samples: 10000,
+            mutations_per_pixel: 1,
+            sigma: 0.01,
+            large_step_probability: 0.3
+        }
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.bidirectional.set_max_depth(max_depth);
+    }
+
+    pub fn set_bootstrap_samples(&mut self, bootstrap_samples: usize) {
+        self.bootstrap_samples = bootstrap_samples;
+    }
+
+    pub fn set_mutations_per_pixel(&mut self, mutations_per_pixel: usize) {
+        self.mutations_per_pixel = mutations_per_pixel;
+    }
+
+    // standard deviation of small steps in primary sample space
+    pub fn set_sigma(&mut self, sigma: f32) {
+        self.sigma = sigma;
+    }
+
+    pub fn set_large_step_probability(&mut self, large_step_probability: f32) {
+        self.large_step_probability = large_step_probability;
+    }
+
+    fn evaluate(&self, camera: &Camera, scene: &Scene, width: u32, height: u32, sampler: &mut MetropolisSampler) -> PathSample {
+        sampler.start_stream(FILM_STREAM);
+
+        let (u, v) = sampler.next_2d();
+        let x = u * width as f32;
+        let y = v * height as f32;
+        let ray: Ray = camera.get_camera_ray_at(x, y, width, height, camera.get_shutter_time(sampler.next_1d()));
+
+        let radiance = self.bidirectional.get_radiance(ray, scene, sampler);
+        let luminance = radiance.get_luminance();
+
+        PathSample {
+            x,
+            y,
+            radiance,
+            luminance: if luminance.is_finite() { f32::max(luminance, 0.) } else { 0. }
+        }
+    }
+
+    fn splat(image: &mut [f32], width: u32, height: u32, sample: &PathSample, weight: f32) {
+        let x = u32::min(sample.x as u32, width - 1);
+        let y = u32::min(sample.y as u32, height - 1);
+        let offset = ((y * width + x) * 3) as usize;
+
+        image[offset] += sample.radiance.r * weight;
+        image[offset + 1] += sample.radiance.g * weight;
+        image[offset + 2] += sample.radiance.b * weight;
+    }
+}
+
+impl Default for MetropolisIntegrator {
+    fn default() -> Self {
+        MetropolisIntegrator::new()
+    }
+}
+
+impl Integrator for MetropolisIntegrator {
+    // without markov chain every pixel simply gets bidirectional estimate
+    fn get_radiance(&self, ray: Ray, scene: &Scene, sampler: &mut dyn Sampler) -> Color {
+        self.bidirectional.get_radiance(ray, scene, sampler)
+    }
+
+    fn is_splatting(&self) -> bool {
+        true
+    }
+
+    fn render_samples(&self, camera: &Camera, scene: &Scene, width: u32, height: u32, sample_count: usize) -> Option<Vec<f32>> {
+        let mut image = vec![0.; (width * height * 3) as usize];
+
+        // bootstrap picks starting state proportionally to its luminance with reservoir sampling
+        let mut luminance_sum = 0.;
+        let mut chain: Option<(MetropolisSampler, PathSample)> = None;
+
+        for _ in 0..self.bootstrap_samples {
+            let mut sampler = MetropolisSampler::new(self.sigma, self.large_step_probability, STREAM_COUNT);
+            let sample = self.evaluate(camera, scene, width, height, &mut sampler);
+
+            if sample.luminance <= 0. {
+                continue;
+            }
+
+            luminance_sum += sample.luminance;
+
+            if rand::random::<f32>() * luminance_sum < sample.luminance {
+                chain = Some((sampler, sample));
+            }
+        }
+
+        let (mut sampler, mut current) = match chain {
+            Some(chain) => chain,
+            None => return Some(image)
+        };
+
+        let brightness = luminance_sum / self.bootstrap_samples as f32;
+        let scale = brightness / self.mutations_per_pixel as f32;
+
+        for _ in 0..sample_count * self.mutations_per_pixel {
+            sampler.start_iteration();
+
+            let proposed = self.evaluate(camera, scene, width, height, &mut sampler);
+            let acceptance = f32::min(1., proposed.luminance / current.luminance);
+
+            // both states are splatted with their expected weights
+            if acceptance > 0. {
+                MetropolisIntegrator::splat(&mut image, width, height, &proposed, acceptance * scale / proposed.luminance);
+            }
+
+            MetropolisIntegrator::splat(&mut image, width, height, &current, (1. - acceptance) * scale / current.luminance);
+
+            if rand::random::<f32>() < acceptance {
+                current = proposed;
+                sampler.accept();
+            } else {
+                sampler.reject();
+            }
+        }
+
+        Some(image)
+    }
+}
@@ -2,5 +2,22 @@ pub mod scene;
 pub mod camera;
 pub mod renderer;
 pub mod light;
+pub mod sky;
+pub mod node;
 pub mod camera_controller;
+pub mod sequence;
+pub mod medium;
+pub mod integrator;
+pub mod bsdf;
+pub mod bidirectional;
+pub mod photon_map;
+pub mod photon_mapping;
+pub mod sampler;
+pub mod metropolis;
+pub mod path_guiding;
+pub mod light_tree;
+pub mod reservoir;
+pub mod denoiser;
+pub mod aov;
+pub mod adaptive_sampling;
 mod thread_pool;
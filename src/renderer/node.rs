@@ -0,0 +1,73 @@
+use crate::renderables::renderable::Renderable;
+use crate::renderer::light::{Light, LightKind};
+use crate::renderer::camera::Camera;
+use crate::math::vec3::Vector3;
+use crate::math::mat4::Matrix4;
+use crate::math::quaternion::Quaternion;
+use std::sync::Arc;
+
+pub struct Node {
+    pub name: String,
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+    pub children: Vec<Node>,
+    pub renderable: Option<Arc<dyn Renderable + Send>>,
+    pub light: Option<Light>,
+    pub camera: Option<Camera>,
+}
+
+impl Node {
+    pub fn new(name: String) -> Self {
+        Node {
+            name,
+            translation: Vector3::new(0., 0., 0.),
+            rotation: Quaternion::identity(),
+            scale: Vector3::new(1., 1., 1.),
+            children: Vec::new(),
+            renderable: None,
+            light: None,
+            camera: None,
+        }
+    }
+
+    pub fn add_child(&mut self, child: Node) {
+        self.children.push(child);
+    }
+
+    pub fn get_local_matrix(&self) -> Matrix4<f32> {
+        Matrix4::compose(&self.translation, &self.rotation, &self.scale)
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Node> {
+        if self.name == name {
+            return Some(self);
+        }
+
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    pub fn get_world_light(light: &Light, world: &Matrix4<f32>) -> Light {
+        let mut light = light.clone();
+
+        light.position.apply_matrix(world);
+
+        if let LightKind::Directional { direction, .. } = &mut light.kind {
+            direction.transform_direction(world);
+        }
+
+        light
+    }
+
+    // walks the hierarchy calling visitor with every node and its world matrix
+    pub fn traverse<F>(&self, parent_world: &Matrix4<f32>, visitor: &mut F) where F: FnMut(&Node, &Matrix4<f32>) {
+        let mut world = *parent_world;
+        world.multiply(&self.get_local_matrix());
+
+        visitor(self, &world);
+
+        for child in &self.children {
+            child.traverse(&world, visitor);
+        }
+    }
+}
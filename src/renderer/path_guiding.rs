@@ -0,0 +1,185 @@
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::f32::consts::PI;
+
+const MIN_CELL_SAMPLES: u32 = 16;
+const UNIFORM_FRACTION: f32 = 0.1;
+const HISTORY_WEIGHT: f32 = 0.5;
+
+// energy accumulated for current frame, f32 stored as bits so that workers can add lock free
+struct Accumulator {
+    energy: Vec<AtomicU32>,
+    samples: Vec<AtomicU32>,
+}
+
+impl Accumulator {
+    fn new(cells: usize, bins: usize) -> Self {
+        Accumulator {
+            energy: (0..cells * bins).map(|_| AtomicU32::new(0)).collect(),
+            samples: (0..cells).map(|_| AtomicU32::new(0)).collect()
+        }
+    }
+
+    fn add(&self, index: usize, value: f32) {
+        let mut current = self.energy[index].load(Ordering::Relaxed);
+
+        loop {
+            let updated = (f32::from_bits(current) + value).to_bits();
+
+            match self.energy[index].compare_exchange_weak(current, updated, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(actual) => current = actual
+            }
+        }
+    }
+
+    fn take(&self, index: usize) -> f32 {
+        f32::from_bits(self.energy[index].swap(0, Ordering::Relaxed))
+    }
+}
+
+// spatial grid over scene bounds with directional histogram in every cell. Directions are binned
+// in cylindrical coordinates (cos theta, phi) so that every bin spans same solid angle.
+// Histograms learned from incident radiance of previous frames are sampled in current one.
+pub struct PathGuide {
+    bbox: BBox,
+    spatial_resolution: usize,
+    directional_resolution: usize,
+    accumulator: Accumulator,
+    distribution: Vec<f32>,
+    cdf: Vec<f32>,
+    trained: Vec<bool>,
+}
+
+impl PathGuide {
+    pub fn new(bbox: BBox, spatial_resolution: usize, directional_resolution: usize) -> Self {
+        let cells = spatial_resolution * spatial_resolution * spatial_resolution;
+        let bins = directional_resolution * directional_resolution;
+
+        PathGuide {
+            bbox,
+            spatial_resolution,
+            directional_resolution,
+            accumulator: Accumulator::new(cells, bins),
+            distribution: vec![1. / bins as f32; cells * bins],
+            cdf: vec![0.; cells * bins],
+            trained: vec![false; cells]
+        }
+    }
+
+    fn get_bins(&self) -> usize {
+        self.directional_resolution * self.directional_resolution
+    }
+
+    fn get_cell(&self, point: &Vector3<f32>) -> usize {
+        let size = self.bbox.get_size();
+        let resolution = self.spatial_resolution;
+        let to_index = |value: f32, min: f32, size: f32| {
+            if size <= 0. { 0 } else { usize::min(f32::max((value - min) / size * resolution as f32, 0.) as usize, resolution - 1) }
+        };
+
+        let x = to_index(point.x, self.bbox.min.x, size.x);
+        let y = to_index(point.y, self.bbox.min.y, size.y);
+        let z = to_index(point.z, self.bbox.min.z, size.z);
+
+        (z * resolution + y) * resolution + x
+    }
+
+    fn get_bin(&self, direction: &Vector3<f32>) -> usize {
+        let resolution = self.directional_resolution;
+        let cos_theta = direction.z.clamp(-1., 1.);
+        let mut phi = f32::atan2(direction.y, direction.x);
+
+        if phi < 0. {
+            phi += 2. * PI;
+        }
+
+        let i = usize::min(((cos_theta + 1.) * 0.5 * resolution as f32) as usize, resolution - 1);
+        let j = usize::min((phi / (2. * PI) * resolution as f32) as usize, resolution - 1);
+
+        i * resolution + j
+    }
+
+    pub fn is_trained(&self, point: &Vector3<f32>) -> bool {
+        self.trained[self.get_cell(point)]
+    }
+
+    // incident radiance estimate arriving at point from direction, already divided by sampling pdf
+    pub fn record(&self, point: &Vector3<f32>, direction: &Vector3<f32>, value: f32) {
+        if !value.is_finite() || value < 0. {
+            return;
+        }
+
+        let cell = self.get_cell(point);
+
+        self.accumulator.add(cell * self.get_bins() + self.get_bin(direction), value);
+        self.accumulator.samples[cell].fetch_add(1, Ordering::Relaxed);
+    }
+
+    // solid angle density of sample returned for the same point
+    pub fn pdf(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> f32 {
+        let cell = self.get_cell(point);
+        let bins = self.get_bins();
+
+        self.distribution[cell * bins + self.get_bin(direction)] * bins as f32 / (4. * PI)
+    }
+
+    pub fn sample(&self, point: &Vector3<f32>, r0: f32, r1: f32, r2: f32) -> Option<(Vector3<f32>, f32)> {
+        let cell = self.get_cell(point);
+
+        if !self.trained[cell] {
+            return None;
+        }
+
+        let bins = self.get_bins();
+        let cdf = &self.cdf[cell * bins..(cell + 1) * bins];
+        let bin = usize::min(cdf.partition_point(|value| *value <= r0), bins - 1);
+
+        let resolution = self.directional_resolution as f32;
+        let i = (bin / self.directional_resolution) as f32;
+        let j = (bin % self.directional_resolution) as f32;
+
+        let cos_theta = -1. + 2. * (i + r1) / resolution;
+        let phi = 2. * PI * (j + r2) / resolution;
+        let sin_theta = f32::sqrt(f32::max(0., 1. - cos_theta * cos_theta));
+
+        let direction = Vector3::new(sin_theta * f32::cos(phi), sin_theta * f32::sin(phi), cos_theta);
+
+        Some((direction, self.distribution[cell * bins + bin] * bins as f32 / (4. * PI)))
+    }
+
+    // turns energy recorded during last frame into sampling distributions and clears it
+    pub fn update(&mut self) {
+        let bins = self.get_bins();
+
+        for cell in 0..self.trained.len() {
+            let samples = self.accumulator.samples[cell].swap(0, Ordering::Relaxed);
+            let energy: Vec<f32> = (0..bins).map(|bin| self.accumulator.take(cell * bins + bin)).collect();
+            let total: f32 = energy.iter().sum();
+
+            if samples < MIN_CELL_SAMPLES || total <= 0. {
+                continue;
+            }
+
+            let history = if self.trained[cell] { HISTORY_WEIGHT } else { 0. };
+            let mut sum = 0.;
+
+            for (bin, bin_energy) in energy.iter().enumerate() {
+                let learned = (1. - UNIFORM_FRACTION) * bin_energy / total + UNIFORM_FRACTION / bins as f32;
+                let index = cell * bins + bin;
+
+                self.distribution[index] = history * self.distribution[index] + (1. - history) * learned;
+                sum += self.distribution[index];
+                self.cdf[index] = sum;
+            }
+
+            for bin in 0..bins {
+                self.cdf[cell * bins + bin] /= sum;
+                self.distribution[cell * bins + bin] /= sum;
+            }
+
+            self.trained[cell] = true;
+        }
+    }
+}
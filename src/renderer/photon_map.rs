@@ -0,0 +1,123 @@
+use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+
+#[derive(Clone, Debug)]
+pub struct Photon {
+    pub position: Vector3<f32>,
+    pub direction: Vector3<f32>,
+    pub power: Color,
+    axis: usize,
+}
+
+impl Photon {
+    // direction points back towards where photon came from
+    pub fn new(position: Vector3<f32>, direction: Vector3<f32>, power: Color) -> Self {
+        Photon {
+            position,
+            direction,
+            power,
+            axis: 0
+        }
+    }
+}
+
+fn get_component(vector: &Vector3<f32>, axis: usize) -> f32 {
+    match axis {
+        0 => vector.x,
+        1 => vector.y,
+        _ => vector.z
+    }
+}
+
+// balanced kd-tree stored implicitly, median of every slice splits it along its widest axis
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+}
+
+impl PhotonMap {
+    pub fn new(photons: Vec<Photon>) -> Self {
+        let mut photons = photons;
+        PhotonMap::build(&mut photons);
+
+        PhotonMap {
+            photons
+        }
+    }
+
+    fn build(photons: &mut [Photon]) {
+        if photons.len() <= 1 {
+            return;
+        }
+
+        let mut min = photons[0].position;
+        let mut max = photons[0].position;
+
+        for photon in photons.iter() {
+            min = Vector3::new(f32::min(min.x, photon.position.x), f32::min(min.y, photon.position.y), f32::min(min.z, photon.position.z));
+            max = Vector3::new(f32::max(max.x, photon.position.x), f32::max(max.y, photon.position.y), f32::max(max.z, photon.position.z));
+        }
+
+        let size = &max - &min;
+        let axis = if size.x >= size.y && size.x >= size.z { 0 } else if size.y >= size.z { 1 } else { 2 };
+        let median = photons.len() / 2;
+
+        photons.select_nth_unstable_by(median, |a, b| {
+            get_component(&a.position, axis).partial_cmp(&get_component(&b.position, axis)).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        photons[median].axis = axis;
+
+        let (left, right) = photons.split_at_mut(median);
+        PhotonMap::build(left);
+        PhotonMap::build(&mut right[1..]);
+    }
+
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    pub fn get_photons(&self) -> &Vec<Photon> {
+        &self.photons
+    }
+
+    pub fn for_each_in_radius<F>(&self, point: &Vector3<f32>, radius: f32, callback: &mut F) where F: FnMut(&Photon) {
+        PhotonMap::search(&self.photons, point, radius * radius, callback);
+    }
+
+    pub fn find_in_radius(&self, point: &Vector3<f32>, radius: f32) -> Vec<&Photon> {
+        let mut found = Vec::new();
+        PhotonMap::search(&self.photons, point, radius * radius, &mut |photon| found.push(photon));
+
+        found
+    }
+
+    fn search<'a, F>(photons: &'a [Photon], point: &Vector3<f32>, radius_squared: f32, callback: &mut F) where F: FnMut(&'a Photon) {
+        if photons.is_empty() {
+            return;
+        }
+
+        let median = photons.len() / 2;
+        let photon = &photons[median];
+        let offset = &photon.position - point;
+
+        if offset.dot(&offset) <= radius_squared {
+            callback(photon);
+        }
+
+        if photons.len() == 1 {
+            return;
+        }
+
+        let distance = get_component(point, photon.axis) - get_component(&photon.position, photon.axis);
+        let (near, far) = if distance < 0. { (&photons[..median], &photons[median + 1..]) } else { (&photons[median + 1..], &photons[..median]) };
+
+        PhotonMap::search(near, point, radius_squared, callback);
+
+        if distance * distance <= radius_squared {
+            PhotonMap::search(far, point, radius_squared, callback);
+        }
+    }
+}
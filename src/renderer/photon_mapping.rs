@@ -0,0 +1,325 @@
+use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+use crate::math::ray::Ray;
+use crate::math::frame::Frame;
+use crate::math::bbox::BBox;
+use crate::renderer::scene::{Scene, Background};
+use crate::renderer::light::{Light, LightKind};
+use crate::renderer::integrator::Integrator;
+use crate::renderer::bsdf::Bsdf;
+use crate::renderer::photon_map::{Photon, PhotonMap};
+use crate::renderer::sampler::Sampler;
+use crate::renderables::renderable::Renderable;
+use std::f32::consts::PI;
+
+const EPS: f32 = 0.0001;
+
+enum Emitter<'a> {
+    Point(&'a Light),
+    Directional(&'a Light, BBox),
+    Environment(BBox),
+    Surface(&'a (dyn Renderable + Send)),
+}
+
+// progressive photon mapping, every pass emits new photon map and shrinks gather radius so that
+// averaging passes converges. Direct light from point and directional lights is sampled explicitly,
+// photons carry light from emissive surfaces and everything that bounced at least once, so caustics
+// are resolved through specular chains from camera. Environment light is emitted from disk covering
+// the scene like light of directional lights.
+pub struct PhotonMapIntegrator {
+    photons_per_pass: usize,
+    initial_radius: f32,
+    radius: f32,
+    alpha: f32,
+    max_depth: usize,
+    pass: usize,
+    photon_map: PhotonMap,
+}
+
+impl PhotonMapIntegrator {
+    pub fn new(radius: f32) -> Self {
+        PhotonMapIntegrator {
+            photons_per_pass: 100000,
+            initial_radius: radius,
+            radius,
+            alpha: 0.7,
+            max_depth: 8,
+            pass: 0,
+            photon_map: PhotonMap::new(Vec::new())
+        }
+    }
+
+    pub fn set_photons_per_pass(&mut self, photons_per_pass: usize) {
+        self.photons_per_pass = photons_per_pass;
+    }
+
+    // fraction of photons kept between passes, lower values shrink radius faster
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha;
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn get_radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn get_photon_map(&self) -> &PhotonMap {
+        &self.photon_map
+    }
+
+    fn is_black(color: &Color) -> bool {
+        color.r <= 0. && color.g <= 0. && color.b <= 0.
+    }
+
+    fn offset_point(point: &Vector3<f32>, normal: &Vector3<f32>, direction: &Vector3<f32>) -> Vector3<f32> {
+        if direction.dot(normal) < 0. { point - &(*normal * EPS) } else { point + &(*normal * EPS) }
+    }
+
+    fn get_emitters(scene: &Scene) -> Vec<Emitter<'_>> {
+        let mut emitters = Vec::new();
+        let bounds = scene.get_bbox();
+
+        for renderable in scene.get_renderables() {
+            if !PhotonMapIntegrator::is_black(&renderable.get_material().emissive_color) {
+                emitters.push(Emitter::Surface(renderable.as_ref()));
+            }
+        }
+
+        for light in scene.get_lights() {
+            match light.kind {
+                LightKind::Point => emitters.push(Emitter::Point(light)),
+                LightKind::Directional { .. } => if let Some(bounds) = &bounds {
+                    emitters.push(Emitter::Directional(light, *bounds));
+                }
+            }
+        }
+
+        let has_environment = match scene.get_background() {
+            Background::Color(color) => !PhotonMapIntegrator::is_black(color),
+            Background::Sky(_) => true
+        };
+
+        if let (true, Some(bounds)) = (has_environment, bounds) {
+            emitters.push(Emitter::Environment(bounds));
+        }
+
+        emitters
+    }
+
+    // ray starting on disk covering bounding sphere of the scene, facing direction, with area of the disk
+    fn emit_into_bounds(bounds: &BBox, direction: Vector3<f32>, time: f32) -> (Ray, f32) {
+        let radius = bounds.get_size().magnitude() * 0.5;
+        let frame = Frame::new(&bounds.get_center() - &(direction * radius), direction);
+
+        let disk_radius = radius * f32::sqrt(rand::random::<f32>());
+        let phi = 2. * PI * rand::random::<f32>();
+        let origin = frame.to_world_point(&Vector3::new(disk_radius * f32::cos(phi), disk_radius * f32::sin(phi), 0.));
+
+        (Ray::new_with_time(origin, direction, time), PI * radius * radius)
+    }
+
+    // returns photon ray with its flux, None when emitter has no samplable surface
+    fn emit(scene: &Scene, emitter: &Emitter, time: f32) -> Option<(Ray, Color)> {
+        match emitter {
+            Emitter::Point(light) => {
+                let z = 1. - 2. * rand::random::<f32>();
+                let radius = f32::sqrt(f32::max(0., 1. - z * z));
+                let phi = 2. * PI * rand::random::<f32>();
+                let direction = Vector3::new(radius * f32::cos(phi), radius * f32::sin(phi), z);
+
+                Some((Ray::new_with_time(light.position, direction, time), light.color * (light.intensity * 4. * PI)))
+            },
+            Emitter::Directional(light, bounds) => {
+                let (towards_light, _) = light.sample_direction(&bounds.get_center(), rand::random(), rand::random());
+                let (ray, area) = PhotonMapIntegrator::emit_into_bounds(bounds, -towards_light, time);
+
+                Some((ray, light.color * (light.intensity * area)))
+            },
+            Emitter::Environment(bounds) => {
+                // uniformly sampled direction towards environment
+                let z = 1. - 2. * rand::random::<f32>();
+                let radius = f32::sqrt(f32::max(0., 1. - z * z));
+                let phi = 2. * PI * rand::random::<f32>();
+                let towards_environment = Vector3::new(radius * f32::cos(phi), radius * f32::sin(phi), z);
+                let (ray, area) = PhotonMapIntegrator::emit_into_bounds(bounds, -towards_environment, time);
+
+                Some((ray, scene.get_background_color(&towards_environment) * (area * 4. * PI)))
+            },
+            Emitter::Surface(renderable) => {
+                let (point, normal, area) = renderable.sample_surface(rand::random(), rand::random(), time)?;
+
+                // cosine weighted emission on side of surface normal
+                let r1 = rand::random::<f32>();
+                let phi = 2. * PI * rand::random::<f32>();
+                let local = Vector3::new(f32::sqrt(r1) * f32::cos(phi), f32::sqrt(r1) * f32::sin(phi), f32::sqrt(1. - r1));
+                let direction = Frame::new(Vector3::new(0., 0., 0.), normal).to_world_direction(&local);
+                let origin = PhotonMapIntegrator::offset_point(&point, &normal, &direction);
+
+                Some((Ray::new_with_time(origin, direction, time), renderable.get_material().emissive_color * (area * PI)))
+            }
+        }
+    }
+
+    fn trace_photons(&self, scene: &Scene) -> Vec<Photon> {
+        let emitters = PhotonMapIntegrator::get_emitters(scene);
+        let mut photons = Vec::new();
+
+        if emitters.is_empty() || self.photons_per_pass == 0 {
+            return photons;
+        }
+
+        let scale = emitters.len() as f32 / self.photons_per_pass as f32;
+
+        for _ in 0..self.photons_per_pass {
+            let index = usize::min((rand::random::<f32>() * emitters.len() as f32) as usize, emitters.len() - 1);
+            let emitter = &emitters[index];
+
+            let (mut ray, power) = match PhotonMapIntegrator::emit(scene, emitter, scene.get_time()) {
+                Some(emitted) => emitted,
+                None => continue
+            };
+
+            // light arriving directly from lights is handled by next event estimation
+            let store_direct = matches!(emitter, Emitter::Surface(_) | Emitter::Environment(_));
+
+            let mut power = power * scale;
+
+            for bounce in 0..self.max_depth {
+                let (intersection_data, renderable) = match scene.intersect(&ray) {
+                    Some(intersection) => intersection,
+                    None => break
+                };
+
+                let point = &ray.origin + &(ray.direction * intersection_data.distance);
+                let bsdf = Bsdf::new(renderable.get_material(), &intersection_data);
+                let wo = -ray.direction;
+
+                if bsdf.get_diffuse_weight() > 0. && (bounce > 0 || store_direct) {
+                    photons.push(Photon::new(point, wo, power));
+                }
+
+                let sample = match bsdf.sample(&wo, rand::random(), rand::random(), rand::random()) {
+                    Some(sample) => sample,
+                    None => break
+                };
+
+                power = power * sample.weight;
+
+                if PhotonMapIntegrator::is_black(&power) {
+                    break;
+                }
+
+                let origin = PhotonMapIntegrator::offset_point(&point, &intersection_data.normal, &sample.direction);
+                ray = Ray::new_with_time(origin, sample.direction, ray.time);
+            }
+        }
+
+        photons
+    }
+
+    fn get_direct_light(scene: &Scene, point: &Vector3<f32>, normal: &Vector3<f32>, wo: &Vector3<f32>, bsdf: &Bsdf, time: f32, sampler: &mut dyn Sampler) -> Color {
+        let mut radiance = Color::new(0., 0., 0.);
+
+        for light in scene.get_lights() {
+            let (direction, distance) = light.sample_direction(point, sampler.next_1d(), sampler.next_1d());
+
+            let contribution = bsdf.evaluate(wo, &direction) * light.get_incident_light(distance) * f32::abs(direction.dot(normal));
+
+            if PhotonMapIntegrator::is_black(&contribution) {
+                continue;
+            }
+
+            let origin = PhotonMapIntegrator::offset_point(point, normal, &direction);
+            let occluded = scene.intersect(&Ray::new_with_time(origin, direction, time))
+                .is_some_and(|(intersection_data, _)| intersection_data.distance < distance - EPS);
+
+            if !occluded {
+                radiance += contribution;
+            }
+        }
+
+        radiance
+    }
+
+    fn estimate_radiance(&self, point: &Vector3<f32>, wo: &Vector3<f32>, bsdf: &Bsdf) -> Color {
+        let mut radiance = Color::new(0., 0., 0.);
+
+        self.photon_map.for_each_in_radius(point, self.radius, &mut |photon| {
+            radiance += bsdf.evaluate(wo, &photon.direction) * photon.power;
+        });
+
+        radiance / (PI * self.radius * self.radius)
+    }
+}
+
+impl Integrator for PhotonMapIntegrator {
+    // progressive estimate starts over with initial radius
+    fn reset(&mut self) {
+        self.radius = self.initial_radius;
+        self.pass = 0;
+    }
+
+    fn prepare(&mut self, scene: &Scene) {
+        if self.pass > 0 {
+            let pass = self.pass as f32;
+            self.radius *= f32::sqrt((pass + self.alpha) / (pass + 1.));
+        }
+
+        self.pass += 1;
+        self.photon_map = PhotonMap::new(self.trace_photons(scene));
+    }
+
+    fn get_radiance(&self, ray: Ray, scene: &Scene, sampler: &mut dyn Sampler) -> Color {
+        let mut ray = ray;
+        let mut throughput = Color::new(1., 1., 1.);
+        let mut radiance = Color::new(0., 0., 0.);
+
+        // camera paths follow specular lobes until diffuse surfaces where photons are gathered
+        for _ in 0..self.max_depth {
+            let (intersection_data, renderable) = match scene.intersect(&ray) {
+                Some(intersection) => intersection,
+                None => {
+                    radiance += throughput * scene.get_background_color(&ray.direction);
+                    break;
+                }
+            };
+
+            let material = renderable.get_material();
+            let point = &ray.origin + &(ray.direction * intersection_data.distance);
+            let bsdf = Bsdf::new(material, &intersection_data);
+            let wo = -ray.direction;
+            let diffuse_weight = bsdf.get_diffuse_weight();
+
+            radiance += throughput * material.emissive_color;
+
+            if diffuse_weight > 0. {
+                let direct_light = PhotonMapIntegrator::get_direct_light(scene, &point, &intersection_data.normal, &wo, &bsdf, ray.time, sampler);
+                radiance += throughput * (direct_light + &self.estimate_radiance(&point, &wo, &bsdf));
+            }
+
+            if diffuse_weight >= 1. {
+                break;
+            }
+
+            let r0 = diffuse_weight + sampler.next_1d() * (1. - diffuse_weight);
+            let sample = match bsdf.sample(&wo, r0, sampler.next_1d(), sampler.next_1d()) {
+                Some(sample) => sample,
+                None => break
+            };
+
+            throughput = throughput * sample.weight * (1. - diffuse_weight);
+
+            if PhotonMapIntegrator::is_black(&throughput) {
+                break;
+            }
+
+            let origin = PhotonMapIntegrator::offset_point(&point, &intersection_data.normal, &sample.direction);
+            ray = Ray::new_with_time(origin, sample.direction, ray.time);
+        }
+
+        radiance
+    }
+}
@@ -3,47 +3,420 @@ use crate::renderables::renderable::{Renderable, IntersectionData};
 use crate::renderer::scene::Scene;
 use crate::renderer::camera::Camera;
 use crate::renderer::thread_pool::ThreadPool;
+use crate::renderer::medium::Medium;
+use crate::renderer::integrator::{Integrator, PathIntegrator};
+use crate::renderer::sampler::RandomSampler;
+use crate::renderer::path_guiding::PathGuide;
+use crate::renderer::reservoir::{Reservoir, ReservoirGrid};
+use crate::renderer::light::Light;
+use crate::renderer::denoiser::{Denoiser, GuideBuffers};
+use crate::renderer::aov::{Aov, AovSample, AovBuffers, get_material_id};
+use crate::renderer::adaptive_sampling::AdaptiveSampling;
+use crate::gl::exr_writer::save_exr;
+use crate::error::Result;
+use crate::renderables::material::Subsurface;
 use crate::math::ray::Ray;
 use crate::math::color::Color;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::f32::consts::PI;
 
 const MAX_DEPTH: usize = 2;
 const INDIRECT_RAYS: usize = 2;
 const REFLECTION_FACTOR: f32 = 0.17;
 const EPS: f32 = 0.0001;
+const MAX_BOUNDARY_CROSSINGS: usize = 16;
+const MAX_SUBSURFACE_STEPS: usize = 256;
+const GUIDING_SPATIAL_RESOLUTION: usize = 16;
+const GUIDING_DIRECTIONAL_RESOLUTION: usize = 16;
+const GUIDING_FRACTION: f32 = 0.5;
+const LIGHT_CANDIDATES: usize = 8;
+const TEMPORAL_HISTORY: f32 = 20.;
+const RESERVOIR_CELLS: f32 = 128.;
+const PROGRESSIVE_START_SCALE: u32 = 8;
+
+// rectangle of pixels being rendered, the rest of image keeps its content
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RenderRegion {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        RenderRegion { x, y, width, height }
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+}
 
 struct RenderScene {
     camera: Camera,
     scene: Scene,
-    image: Vec<f32>
+    integrator: Box<dyn Integrator>,
+    image: Vec<f32>,
+    guides: Option<GuideBuffers>,
+    aovs: Option<AovBuffers>,
+    sample_counts: Vec<u32>,
+    luminance_squares: Vec<f32>,
+    pixel_samples: Vec<u32>
 }
 
 pub struct Renderer {
     width: u32,
     height: u32,
     frames_total: u32,
-    last_frame_camera_position: Vector3<f32>,
+    last_frame_camera_version: u32,
+    last_frame_scene_version: u32,
     thread_pool: ThreadPool,
+    denoiser: Option<Denoiser>,
+    denoised_image: Vec<f32>,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    render_region: Option<RenderRegion>,
+    progressive: bool,
+    // resolution divider of next frame and of the last rendered one
+    progressive_scale: u32,
+    preview_scale: u32,
+    preview_image: Vec<f32>,
     render_scene: Arc<RenderScene>
 }
 
 impl Renderer {
     pub fn new(width: u32, height: u32, camera: Camera, scene: Scene) -> Self {
         let image: Vec<f32> = vec![0.; ((width * height) * 3) as usize];
+        let pixels = (width * height) as usize;
 
         Renderer {
             width,
             height,
             frames_total: 0,
             thread_pool: ThreadPool::new(),
-            last_frame_camera_position: Vector3::new(0.,0.,0.),
-            render_scene: Arc::new(RenderScene { image, scene, camera })
+            last_frame_camera_version: 0,
+            last_frame_scene_version: 0,
+            denoiser: None,
+            denoised_image: Vec::new(),
+            adaptive_sampling: None,
+            render_region: None,
+            progressive: false,
+            progressive_scale: 1,
+            preview_scale: 1,
+            preview_image: Vec::new(),
+            render_scene: Arc::new(RenderScene {
+                image,
+                scene,
+                camera,
+                integrator: Box::new(PathIntegrator::new()),
+                guides: None,
+                aovs: None,
+                sample_counts: vec![0; pixels],
+                luminance_squares: vec![0.; pixels],
+                pixel_samples: vec![1; pixels]
+            })
         }
     }
 
     pub fn get_render_camera(&mut self) -> &mut Camera {&mut Arc::get_mut(&mut self.render_scene).unwrap().camera}
 
+    // changes made through scene methods reset accumulation on next render
+    pub fn get_scene_mut(&mut self) -> &mut Scene {
+        &mut Arc::get_mut(&mut self.render_scene).unwrap().scene
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    // upsampled while progressive rendering is below full resolution, denoised when denoiser is set
+    pub fn get_image(&self) -> &Vec<f32> {
+        if self.preview_scale > 1 {
+            return &self.preview_image;
+        }
+
+        match self.denoiser {
+            Some(_) => &self.denoised_image,
+            None => &self.render_scene.image
+        }
+    }
+
+    pub fn get_raw_image(&self) -> &Vec<f32> {
+        &self.render_scene.image
+    }
+
+    pub fn get_guides(&self) -> Option<&GuideBuffers> {
+        self.render_scene.guides.as_ref()
+    }
+
+    pub fn get_aovs(&self) -> Option<&AovBuffers> {
+        self.render_scene.aovs.as_ref()
+    }
+
+    // passes accumulated from now on, empty list turns them off
+    pub fn set_aovs(&mut self, aovs: &[Aov]) {
+        let buffers = if aovs.is_empty() { None } else { Some(AovBuffers::new(aovs, self.width, self.height)) };
+        Arc::get_mut(&mut self.render_scene).unwrap().aovs = buffers;
+
+        self.reset();
+    }
+
+    // multilayer exr with beauty in R, G, B and every pass as its own layer, radiance is scaled so that 255 maps to 1
+    pub fn save_exr(&self, path: &str) -> Result<()> {
+        let pixels = (self.width * self.height) as usize;
+        let planar = |buffer: &Vec<f32>, channels: usize, c: usize, scale: f32| -> Vec<f32> {
+            (0..pixels).map(|pixel| buffer[pixel * channels + c] * scale).collect()
+        };
+
+        let mut planes: Vec<(String, Vec<f32>)> = ["R", "G", "B"].iter().enumerate()
+            .map(|(c, name)| (String::from(*name), planar(self.get_image(), 3, c, 1. / 255.)))
+            .collect();
+
+        if let Some(aovs) = self.get_aovs() {
+            for aov in aovs.get_aovs() {
+                let buffer = aovs.get(*aov).unwrap();
+                let scale = if aov.is_radiance() { 1. / 255. } else { 1. };
+
+                for (c, channel) in aov.get_channel_names().iter().enumerate() {
+                    planes.push((format!("{}.{}", aov.get_name(), channel), planar(buffer, aov.get_channels(), c, scale)));
+                }
+            }
+        }
+
+        let channels: Vec<(String, &[f32])> = planes.iter().map(|(name, values)| (name.clone(), values.as_slice())).collect();
+
+        save_exr(path, self.width, self.height, &channels)
+    }
+
+    pub fn reset(&mut self) {
+        let render_scene = Arc::get_mut(&mut self.render_scene).unwrap();
+
+        self.frames_total = 0;
+        self.progressive_scale = if self.progressive { PROGRESSIVE_START_SCALE } else { 1 };
+        self.last_frame_camera_version = render_scene.camera.get_version();
+        self.last_frame_scene_version = render_scene.scene.get_version();
+        render_scene.integrator.reset();
+        for i in 0..render_scene.image.len() { render_scene.image[i] = 0.; }
+        for i in 0..render_scene.sample_counts.len() { render_scene.sample_counts[i] = 0; }
+        for i in 0..render_scene.luminance_squares.len() { render_scene.luminance_squares[i] = 0.; }
+
+        if let Some(guides) = &mut render_scene.guides {
+            guides.clear();
+        }
+
+        if let Some(aovs) = &mut render_scene.aovs {
+            aovs.clear();
+        }
+    }
+
+    pub fn set_integrator(&mut self, integrator: Box<dyn Integrator>) {
+        Arc::get_mut(&mut self.render_scene).unwrap().integrator = integrator;
+
+        self.reset();
+    }
+
+    // learns incident light over frames and steers indirect bounces of the path tracer towards it
+    pub fn set_path_guiding(&mut self, enabled: bool) {
+        let scene = &mut Arc::get_mut(&mut self.render_scene).unwrap().scene;
+
+        let path_guide = if enabled {
+            scene.get_bbox().map(|bbox| PathGuide::new(bbox, GUIDING_SPATIAL_RESOLUTION, GUIDING_DIRECTIONAL_RESOLUTION))
+        } else {
+            None
+        };
+
+        scene.set_path_guide(path_guide);
+        self.reset();
+    }
+
+    // picks one light per shading point with light tree and reservoir resampling reused across frames
+    pub fn set_resampled_lighting(&mut self, enabled: bool) {
+        let scene = &mut Arc::get_mut(&mut self.render_scene).unwrap().scene;
+
+        let light_reservoirs = if enabled {
+            let cell_size = scene.get_bbox().map_or(1., |bbox| f32::max(bbox.get_size().magnitude() / RESERVOIR_CELLS, EPS));
+            Some(ReservoirGrid::new(cell_size))
+        } else {
+            None
+        };
+
+        scene.set_light_reservoirs(light_reservoirs);
+        self.reset();
+    }
+
+    // filters output image guided by albedo, normal and depth of first hits gathered while it is set
+    pub fn set_denoiser(&mut self, denoiser: Option<Denoiser>) {
+        let guides = denoiser.as_ref().map(|_| GuideBuffers::new(self.width, self.height));
+        let render_scene = Arc::get_mut(&mut self.render_scene).unwrap();
+
+        render_scene.guides = guides;
+        self.denoised_image = render_scene.image.clone();
+        self.denoiser = denoiser;
+
+        self.reset();
+    }
+
+    // pixels stop receiving samples once relative error of their luminance falls under threshold
+    pub fn set_adaptive_sampling(&mut self, adaptive_sampling: Option<AdaptiveSampling>) {
+        self.adaptive_sampling = adaptive_sampling;
+
+        self.reset();
+    }
+
+    // restricts rendering to region of image, None renders whole image
+    pub fn set_render_region(&mut self, render_region: Option<RenderRegion>) {
+        self.render_region = render_region.map(|region| {
+            let x = u32::min(region.x, self.width);
+            let y = u32::min(region.y, self.height);
+
+            RenderRegion::new(x, y, u32::min(region.width, self.width - x), u32::min(region.height, self.height - y))
+        });
+    }
+
+    pub fn get_render_region(&self) -> Option<RenderRegion> {
+        self.render_region
+    }
+
+    fn is_in_region(&self, pixel: usize) -> bool {
+        self.render_region.is_none_or(|region| region.contains(pixel as u32 % self.width, pixel as u32 / self.width))
+    }
+
+    // after every reset frames are rendered at 1/8, 1/4 and 1/2 of resolution and upsampled before
+    // full resolution refinement starts, so that moving camera stays responsive
+    pub fn set_progressive(&mut self, progressive: bool) {
+        self.progressive = progressive;
+
+        self.reset();
+    }
+
+    // resolution divider of last rendered frame
+    pub fn get_preview_scale(&self) -> u32 {
+        self.preview_scale
+    }
+
+    pub fn get_sample_counts(&self) -> &Vec<u32> {
+        &self.render_scene.sample_counts
+    }
+
+    fn get_pixel_luminance(&self, pixel: usize) -> f32 {
+        let image = &self.render_scene.image;
+
+        Color::new(image[pixel * 3], image[pixel * 3 + 1], image[pixel * 3 + 2]).get_luminance()
+    }
+
+    // always false without adaptive sampling
+    pub fn is_converged(&self) -> bool {
+        let render_scene = &self.render_scene;
+
+        match &self.adaptive_sampling {
+            Some(adaptive_sampling) => (0..render_scene.sample_counts.len()).filter(|pixel| self.is_in_region(*pixel)).all(|pixel| {
+                adaptive_sampling.is_converged(self.get_pixel_luminance(pixel), render_scene.luminance_squares[pixel], render_scene.sample_counts[pixel])
+            }),
+            None => false
+        }
+    }
+
+    // headless rendering until noise threshold is reached, returns count of rendered frames
+    pub fn render_until_converged(&mut self, max_frames: u32) -> u32 {
+        for frame in 0..max_frames {
+            if self.is_converged() {
+                return frame;
+            }
+
+            self.render();
+        }
+
+        max_frames
+    }
+
+    // pixel sampled for preview block containing given pixel of render region, blocks start at region corner
+    fn get_block_source(&self, pixel: usize, scale: u32) -> usize {
+        let (origin_x, origin_y) = self.render_region.map_or((0, 0), |region| (region.x as usize, region.y as usize));
+        let width = self.width as usize;
+        let scale = scale as usize;
+
+        let x = pixel % width - (pixel % width - origin_x) % scale;
+        let y = pixel / width - (pixel / width - origin_y) % scale;
+
+        y * width + x
+    }
+
+    fn plan_pixel_samples(&mut self, scale: u32) {
+        let pixels = self.render_scene.sample_counts.len();
+
+        // reduced resolution samples only one pixel of every block
+        let sampled: Vec<usize> = (0..pixels).filter(|pixel| {
+            self.is_in_region(*pixel) && (scale == 1 || self.get_block_source(*pixel, scale) == *pixel)
+        }).collect();
+        let luminance: Vec<f32> = match &self.adaptive_sampling {
+            Some(_) if !self.render_scene.integrator.is_splatting() => (0..pixels).map(|pixel| self.get_pixel_luminance(pixel)).collect(),
+            _ => Vec::new()
+        };
+
+        let render_scene = Arc::get_mut(&mut self.render_scene).unwrap();
+
+        for i in 0..pixels { render_scene.pixel_samples[i] = 0; }
+
+        // samples of one frame are shared only by sampled pixels
+        match &self.adaptive_sampling {
+            Some(adaptive_sampling) if !luminance.is_empty() => {
+                adaptive_sampling.plan(&sampled, &luminance, &render_scene.luminance_squares, &render_scene.sample_counts, &mut render_scene.pixel_samples);
+            }
+            _ => {
+                for pixel in sampled { render_scene.pixel_samples[pixel] = 1; }
+            }
+        }
+    }
+
+    // every pixel takes color of sampled pixel of its block, pixels outside of region keep accumulated image.
+    // Guides are upsampled in the same way so that preview is denoised too.
+    fn update_preview(&mut self, scale: u32) {
+        let image = &self.render_scene.image;
+        let mut preview_image = image.clone();
+        let mut preview_guides = self.render_scene.guides.as_ref().map(|_| GuideBuffers::new(self.width, self.height));
+
+        for pixel in 0..image.len() / 3 {
+            if !self.is_in_region(pixel) {
+                continue;
+            }
+
+            let source = self.get_block_source(pixel, scale);
+
+            for c in 0..3 {
+                preview_image[pixel * 3 + c] = image[source * 3 + c];
+            }
+
+            if let (Some(preview_guides), Some(guides)) = (&mut preview_guides, &self.render_scene.guides) {
+                for c in 0..3 {
+                    preview_guides.albedo[pixel * 3 + c] = guides.albedo[source * 3 + c];
+                    preview_guides.normal[pixel * 3 + c] = guides.normal[source * 3 + c];
+                }
+
+                preview_guides.depth[pixel] = guides.depth[source];
+            }
+        }
+
+        self.preview_image = match (&self.denoiser, &preview_guides) {
+            (Some(denoiser), Some(guides)) => denoiser.denoise(&preview_image, guides, self.width, self.height, self.frames_total),
+            _ => preview_image
+        };
+    }
+
+    // moves scene and camera to given time, shutter stays open for shutter_duration after it
+    pub fn set_time(&mut self, time: f32, shutter_duration: f32) {
+        let render_scene = Arc::get_mut(&mut self.render_scene).unwrap();
+
+        render_scene.scene.set_time(time);
+        render_scene.camera.set_time(time);
+        render_scene.camera.set_shutter(time, time + shutter_duration);
+
+        self.reset();
+    }
+
     fn create_scatter_direction(normal: &Vector3<f32>, r1: f32, r2: f32) -> Vector3<f32> {
         let y = r1;
         let azimuth = r2 * 2.0 * PI;
@@ -64,22 +437,49 @@ impl Renderer {
         scatter
     }
 
-    fn calculate_indirect_light(ray: &Ray, scene: &Scene, intersection_data: &IntersectionData, depth: usize) -> Color {
+    // returns scatter direction with its solid angle density, guided directions are mixed with uniform hemisphere
+    fn create_guided_direction(scene: &Scene, point: &Vector3<f32>, normal: &Vector3<f32>) -> (Vector3<f32>, f32) {
+        let uniform_pdf = 1. / (2. * PI);
+
+        let guide = match scene.get_path_guide() {
+            Some(guide) if guide.is_trained(point) => guide,
+            _ => return (Renderer::create_scatter_direction(normal, rand::random(), rand::random()), uniform_pdf)
+        };
+
+        let direction = if rand::random::<f32>() < GUIDING_FRACTION {
+            guide.sample(point, rand::random(), rand::random(), rand::random()).unwrap().0
+        } else {
+            Renderer::create_scatter_direction(normal, rand::random(), rand::random())
+        };
+
+        let hemisphere_pdf = if direction.dot(normal) > 0. { uniform_pdf } else { 0. };
+
+        (direction, GUIDING_FRACTION * guide.pdf(point, &direction) + (1. - GUIDING_FRACTION) * hemisphere_pdf)
+    }
+
+    fn calculate_indirect_light(ray: &Ray, scene: &Scene, intersection_data: &IntersectionData, depth: usize, medium: Option<&Medium>) -> Color {
         let mut indirect_light = Color::new(0.,0.,0.);
 
         let hit_point = &ray.origin + &(ray.direction * intersection_data.distance);
 
         let indirect_count = usize::max(INDIRECT_RAYS >> depth, 1);
         for _i in 0..indirect_count {
-            let r1 = rand::random();
-            let r2 = rand::random();
+            let (direction, pdf) = Renderer::create_guided_direction(scene, &hit_point, &intersection_data.normal);
+            let cosine_angle = direction.dot(&intersection_data.normal);
 
-            let direction = Renderer::create_scatter_direction(&intersection_data.normal, r1, r2);
+            if cosine_angle <= 0. || pdf <= 0. {
+                continue;
+            }
 
-            let indirect_ray = Ray::new(&hit_point + &(direction * 0.0001), direction);
-            let cosine_angle = direction.dot(&intersection_data.normal);
+            let indirect_ray = Ray::new_with_time(&hit_point + &(direction * 0.0001), direction, ray.time);
+            let incoming = Renderer::trace(indirect_ray,  scene, depth + 1, medium);
 
-            indirect_light += Renderer::trace(indirect_ray,  &scene, depth + 1) * cosine_angle;
+            if let Some(guide) = scene.get_path_guide() {
+                guide.record(&hit_point, &direction, incoming.get_luminance() * cosine_angle / pdf);
+            }
+
+            // weighted to keep the same expectation as uniform hemisphere sampling
+            indirect_light += incoming * (cosine_angle / (2. * PI * pdf));
         }
 
         indirect_light = indirect_light * f32::powf(REFLECTION_FACTOR, depth as f32);
@@ -87,7 +487,7 @@ impl Renderer {
         return indirect_light / indirect_count as f32;
     }
 
-    fn calculate_direct_light(ray: &Ray, scene: &Scene, hit_point: &Vector3<f32>, intersection_data: &IntersectionData) -> f32 {
+    fn calculate_direct_light(ray: &Ray, scene: &Scene, hit_point: &Vector3<f32>, intersection_data: &IntersectionData, medium: Option<&Medium>) -> f32 {
         let normal = intersection_data.normal;
 
         let shadow_point;
@@ -97,18 +497,18 @@ impl Renderer {
             shadow_point = hit_point - &(normal* EPS);
         }
 
+        if let Some(light) = Renderer::calculate_resampled_direct_light(ray, scene, hit_point, &shadow_point, &normal, medium) {
+            return light;
+        }
+
         let mut diffuse = 0.0;
 
         for light in scene.get_lights() {
-            let mut light_direction = &light.position - hit_point;
-            light_direction.normalize();
+            let (light_direction, light_distance) = light.sample_direction(hit_point, rand::random(), rand::random());
 
-            let shadow_ray = Ray::new(shadow_point, light_direction);
+            let shadow_ray = Ray::new_with_time(shadow_point, light_direction, ray.time);
 
-            let in_light = match Renderer::check_intersections(&shadow_ray, &scene) {
-                Some(_) => 0.,
-                None => 1.
-            };
+            let in_light = Renderer::get_shadow_transmittance(shadow_ray, scene, light_distance, medium);
 
             let light_to_normal = f32::max(0., light_direction.dot(&normal));
 
@@ -118,6 +518,182 @@ impl Renderer {
         diffuse
     }
 
+    // unshadowed contribution of light used as resampling target
+    fn get_light_target(light: &Light, point: &Vector3<f32>, normal: &Vector3<f32>) -> f32 {
+        let (light_direction, _) = light.sample_direction(point, 0., 0.);
+
+        f32::max(light.intensity, 0.) * f32::max(0., light_direction.dot(normal))
+    }
+
+    fn calculate_resampled_direct_light(ray: &Ray, scene: &Scene, hit_point: &Vector3<f32>, shadow_point: &Vector3<f32>, normal: &Vector3<f32>, medium: Option<&Medium>) -> Option<f32> {
+        let (light_reservoirs, light_tree) = match (scene.get_light_reservoirs(), scene.get_light_tree()) {
+            (Some(light_reservoirs), Some(light_tree)) => (light_reservoirs, light_tree),
+            _ => return None
+        };
+
+        let lights = scene.get_lights();
+        let mut reservoir = Reservoir::new();
+
+        for _ in 0..LIGHT_CANDIDATES {
+            match light_tree.sample(lights, hit_point, rand::random()) {
+                Some((index, probability)) => {
+                    let target = Renderer::get_light_target(&lights[index], hit_point, normal);
+                    reservoir.update(index, target / probability, target, 1., rand::random());
+                },
+                None => return Some(0.)
+            }
+        }
+
+        // temporal reuse, history is clamped so that stale lights fade out
+        let previous = light_reservoirs.get(hit_point, normal);
+
+        if !previous.is_empty() && previous.light < lights.len() {
+            let target = Renderer::get_light_target(&lights[previous.light], hit_point, normal);
+            let count = f32::min(previous.count, TEMPORAL_HISTORY * LIGHT_CANDIDATES as f32);
+
+            reservoir.update(previous.light, target * previous.get_weight() * count, target, count, rand::random());
+        }
+
+        light_reservoirs.store(hit_point, normal, &reservoir, rand::random());
+
+        let weight = reservoir.get_weight();
+
+        if weight <= 0. {
+            return Some(0.);
+        }
+
+        let light = &lights[reservoir.light];
+        let (light_direction, light_distance) = light.sample_direction(hit_point, rand::random(), rand::random());
+
+        let shadow_ray = Ray::new_with_time(*shadow_point, light_direction, ray.time);
+        let in_light = Renderer::get_shadow_transmittance(shadow_ray, scene, light_distance, medium);
+
+        Some(in_light * light.intensity * f32::max(0., light_direction.dot(normal)) * weight)
+    }
+
+    // fraction of light passing to max_distance, medium boundaries are crossed while other surfaces block
+    fn get_shadow_transmittance<'a>(ray: Ray, scene: &'a Scene, max_distance: f32, medium: Option<&'a Medium>) -> f32 {
+        let mut ray = ray;
+        let mut medium = medium;
+        let mut remaining = max_distance;
+        let mut transmittance = 1.;
+
+        for _ in 0..MAX_BOUNDARY_CROSSINGS {
+            let intersection = Renderer::check_intersections(&ray, scene)
+                .filter(|(intersection_data, _)| intersection_data.distance < remaining);
+
+            let segment = intersection.as_ref().map_or(remaining, |(intersection_data, _)| intersection_data.distance);
+
+            if let Some(medium) = medium {
+                transmittance *= medium.get_transmittance(&ray, segment);
+            }
+
+            let (intersection_data, renderable) = match intersection {
+                Some(intersection) => intersection,
+                None => return transmittance
+            };
+
+            medium = match Renderer::get_medium_behind(&ray, &intersection_data, renderable.get_material().medium.as_ref(), scene) {
+                Some(next_medium) => next_medium,
+                None => return 0.
+            };
+
+            let hit_point = &ray.origin + &(ray.direction * (intersection_data.distance + EPS));
+            ray = Ray::new_with_time(hit_point, ray.direction, ray.time);
+            remaining -= intersection_data.distance + EPS;
+        }
+
+        0.
+    }
+
+    // surfaces with medium are index matched boundaries, returns medium on the other side or None for opaque surface
+    fn get_medium_behind<'a>(ray: &Ray, intersection_data: &IntersectionData, boundary: Option<&'a Arc<Medium>>, scene: &'a Scene) -> Option<Option<&'a Medium>> {
+        boundary.map(|inner| {
+            if ray.direction.dot(&intersection_data.normal) < 0. {
+                Some(inner.as_ref())
+            } else {
+                scene.get_medium()
+            }
+        })
+    }
+
+    // random walk inside the renderable for one randomly picked color channel
+    fn calculate_subsurface_light(ray: &Ray, scene: &Scene, intersection_data: &IntersectionData, renderable: &(dyn Renderable + Send), subsurface: &Subsurface, depth: usize, medium: Option<&Medium>) -> Color {
+        let channel = usize::min((rand::random::<f32>() * 3.) as usize, 2);
+        let extinction = subsurface.get_extinction(channel);
+        let albedo = subsurface.get_single_scattering_albedo(channel);
+        let phase = Medium::new_homogeneous(0., extinction, Color::new(255., 255., 255.), subsurface.anisotropy);
+
+        let mut normal = intersection_data.normal;
+        if ray.direction.dot(&normal) > 0. {
+            normal = -normal;
+        }
+
+        let entry_point = &ray.origin + &(ray.direction * intersection_data.distance);
+        let direction = Renderer::create_scatter_direction(&-normal, rand::random(), rand::random());
+        let mut walk_ray = Ray::new_with_time(&entry_point - &(normal * EPS), direction, ray.time);
+
+        for _ in 0..MAX_SUBSURFACE_STEPS {
+            let distance = -f32::ln(1. - rand::random::<f32>()) / extinction;
+
+            match renderable.intersects(&walk_ray) {
+                Some(exit) if exit.distance < distance => {
+                    let mut exit_normal = exit.normal;
+                    if exit_normal.dot(&walk_ray.direction) < 0. {
+                        exit_normal = -exit_normal;
+                    }
+
+                    // shading at exit point as seen by virtual ray arriving from outside along the normal
+                    let exit_point = &walk_ray.origin + &(walk_ray.direction * exit.distance);
+                    let outside_ray = Ray::new_with_time(&exit_point + &exit_normal, -exit_normal, ray.time);
+                    let exit_data = IntersectionData { distance: 1., normal: exit_normal, uv: exit.uv, vertex_color: exit.vertex_color };
+
+                    let direct_light = Renderer::calculate_direct_light(&outside_ray, scene, &exit_point, &exit_data, medium);
+                    let light = Renderer::calculate_indirect_light(&outside_ray, scene, &exit_data, depth, medium) + &(Color::new(255., 255., 255.) * direct_light);
+
+                    // one channel carries whole estimate, so it is weighted by inverse pick probability
+                    return match channel {
+                        0 => Color::new(light.r * 3., 0., 0.),
+                        1 => Color::new(0., light.g * 3., 0.),
+                        _ => Color::new(0., 0., light.b * 3.)
+                    };
+                },
+                _ => {}
+            }
+
+            if rand::random::<f32>() > albedo {
+                break;
+            }
+
+            let scatter_point = &walk_ray.origin + &(walk_ray.direction * distance);
+            let scattered_direction = phase.sample_phase(&walk_ray.direction, rand::random(), rand::random());
+            walk_ray = Ray::new_with_time(scatter_point, scattered_direction, ray.time);
+        }
+
+        Color::new(0., 0., 0.)
+    }
+
+    fn calculate_medium_scattering(ray: &Ray, scene: &Scene, depth: usize, medium: &Medium, distance: f32) -> Color {
+        let point = &ray.origin + &(ray.direction * distance);
+        let mut direct_light = 0.;
+
+        for light in scene.get_lights() {
+            let (light_direction, light_distance) = light.sample_direction(&point, rand::random(), rand::random());
+            let shadow_ray = Ray::new_with_time(point, light_direction, ray.time);
+
+            let in_light = Renderer::get_shadow_transmittance(shadow_ray, scene, light_distance, Some(medium));
+
+            // phase is scaled so isotropic media receive light like a surface facing it
+            direct_light += in_light * light.intensity * medium.get_phase(ray.direction.dot(&light_direction)) * 4. * PI;
+        }
+
+        let scattered_direction = medium.sample_phase(&ray.direction, rand::random(), rand::random());
+        let scattered_ray = Ray::new_with_time(point, scattered_direction, ray.time);
+        let indirect_light = Renderer::trace(scattered_ray, scene, depth + 1, Some(medium)) * (medium.color / 255.);
+
+        (indirect_light + &(medium.color * direct_light)) * medium.get_albedo()
+    }
+
     fn create_coordinate_system(normal: &Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
         let mut n_t = if normal.x.abs() > normal.y.abs() {
             Vector3::new(normal.z, 0.0, -normal.x)
@@ -133,113 +709,362 @@ impl Renderer {
         (n_t, n_b)
     }
 
-    fn create_reflection_ray(normal: &Vector3<f32>, incident: &Vector3<f32>, intersection: &Vector3<f32>) -> Ray {
+    fn create_reflection_ray(normal: &Vector3<f32>, incident: &Vector3<f32>, intersection: &Vector3<f32>, roughness: f32, time: f32) -> Ray {
+        let mirror_direction = incident - &(*normal  * 2.0 * incident.clone().dot(normal));
+        let mut direction = mirror_direction;
+
+        // rough surfaces scatter reflection around the mirror direction
+        if roughness > 0. {
+            let mut glossy_direction = &mirror_direction + &(Renderer::random_in_unit_sphere() * roughness);
+            glossy_direction.normalize();
+
+            if glossy_direction.dot(normal) * mirror_direction.dot(normal) > 0. {
+                direction = glossy_direction;
+            }
+        }
+
         Ray {
             origin: intersection + &(*normal * EPS),
-            direction: incident - &(*normal  * 2.0 * incident.clone().dot(&normal)),
+            direction,
+            time,
         }
     }
 
-    fn check_intersections<'a>(ray: &Ray, scene: &'a Scene) -> Option<(IntersectionData, &'a Box<dyn Renderable + Send + 'static>)> {
-        let mut near = std::f32::INFINITY;
-        let mut intersected_renderable = None;
-        let mut result_intersected_data: Option<IntersectionData> = None;
+    fn create_refraction_ray(normal: &Vector3<f32>, incident: &Vector3<f32>, intersection: &Vector3<f32>, refractive_index: f32, time: f32) -> Option<Ray> {
+        let mut cos_incident = incident.dot(normal);
 
-        for renderable in scene.get_renderables() {
-            match renderable.intersects(&ray) {
-                Some(intersection_data) => {
-                    if intersection_data.distance < near {
-                        near = intersection_data.distance;
-                        result_intersected_data = Some(intersection_data);
-                        intersected_renderable = Some(renderable);
-                    }
-                },
-                None => {},
-            }
+        let (eta, refraction_normal) = if cos_incident < 0. {
+            cos_incident = -cos_incident;
+            (1. / refractive_index, *normal)
+        } else {
+            (refractive_index, -*normal)
+        };
+
+        let k = 1. - eta * eta * (1. - cos_incident * cos_incident);
+
+        if k < 0. {
+            return None;
         }
 
-        return match result_intersected_data {
-            Some(data) => Some((data, &intersected_renderable.unwrap())),
-            None => None
+        let mut direction = &(*incident * eta) + &(refraction_normal * (eta * cos_incident - f32::sqrt(k)));
+        direction.normalize();
+
+        Some(Ray {
+            origin: intersection - &(refraction_normal * EPS),
+            direction,
+            time,
+        })
+    }
+
+    fn random_in_unit_sphere() -> Vector3<f32> {
+        loop {
+            let point = Vector3::new(
+                rand::random::<f32>() * 2. - 1.,
+                rand::random::<f32>() * 2. - 1.,
+                rand::random::<f32>() * 2. - 1.
+            );
+
+            if point.dot(&point) < 1. {
+                return point;
+            }
         }
     }
 
-    fn trace(ray: Ray, scene: &Scene, depth: usize) -> Color {
+    fn check_intersections<'a>(ray: &Ray, scene: &'a Scene) -> Option<(IntersectionData, &'a (dyn Renderable + Send))> {
+        scene.intersect(ray)
+    }
+
+    pub(crate) fn trace(ray: Ray, scene: &Scene, depth: usize, medium: Option<&Medium>) -> Color {
+        Renderer::trace_with_aovs(ray, scene, depth, medium, None)
+    }
+
+    // aovs are written only for hit of camera ray, deeper bounces end up in its lighting passes
+    pub(crate) fn trace_with_aovs(ray: Ray, scene: &Scene, depth: usize, medium: Option<&Medium>, aovs: Option<&mut AovSample>) -> Color {
         let mut pixel_color = Color::new(0.,0.,0.);
 
         if depth > MAX_DEPTH {
             return pixel_color;
         }
 
-        match Renderer::check_intersections(&ray, &scene) {
+        let intersection = Renderer::check_intersections(&ray, scene);
+
+        if let Some(medium) = medium {
+            let surface_distance = intersection.as_ref().map_or(f32::INFINITY, |(intersection_data, _)| intersection_data.distance);
+
+            if let Some(distance) = medium.sample_distance(&ray, surface_distance) {
+                let scattered = Renderer::calculate_medium_scattering(&ray, scene, depth, medium, distance);
+
+                if let Some(aovs) = aovs {
+                    Renderer::record_background(&ray, aovs);
+                    aovs.depth = distance;
+                    aovs.diffuse_indirect = scattered;
+                }
+
+                return scattered;
+            }
+        }
+
+        match intersection {
             Some((result_intersected_data, renderable)) => {
                 let hit_point = &ray.origin + &(ray.direction * result_intersected_data.distance);
+                let material = renderable.get_material();
+
+                if let Some(next_medium) = Renderer::get_medium_behind(&ray, &result_intersected_data, material.medium.as_ref(), scene) {
+                    let continued_ray = Ray::new_with_time(&hit_point + &(ray.direction * EPS), ray.direction, ray.time);
+                    return Renderer::trace_with_aovs(continued_ray, scene, depth, next_medium, aovs);
+                }
 
-                let direct_light = Renderer::calculate_direct_light(&ray, &scene, &hit_point,&result_intersected_data);
-                let indirect_light_color = Renderer::calculate_indirect_light(&ray, &scene, &result_intersected_data, depth);
+                // subsurface light counts as indirect diffuse
+                let (diffuse_direct, diffuse_indirect) = match &material.subsurface {
+                    Some(subsurface) => {
+                        let subsurface_light = Renderer::calculate_subsurface_light(&ray, scene, &result_intersected_data, renderable, subsurface, depth, medium);
+                        (Color::new(0., 0., 0.), subsurface_light)
+                    },
+                    None => {
+                        let direct_light = Renderer::calculate_direct_light(&ray, scene, &hit_point,&result_intersected_data, medium);
+                        let indirect_light_color = Renderer::calculate_indirect_light(&ray, scene, &result_intersected_data, depth, medium);
 
-                let material = renderable.get_material();
+                        let mut diffuse_color = material.get_diffuse_color(result_intersected_data.uv);
 
-                let diffuse_color = material.diffuse_color;
-                pixel_color = indirect_light_color + &(diffuse_color * direct_light);
+                        if let Some(vertex_color) = result_intersected_data.vertex_color {
+                            diffuse_color = diffuse_color * (vertex_color / 255.);
+                        }
+
+                        (diffuse_color * direct_light, indirect_light_color)
+                    }
+                };
+
+                pixel_color = diffuse_indirect + &diffuse_direct;
+                pixel_color += material.emissive_color;
+
+                // share of surface shading left after transparency and reflection
+                let mut surface_weight = 1.;
+                let mut specular = Color::new(0., 0., 0.);
+
+                if material.opacity < 1. {
+                    let normal = &result_intersected_data.normal;
+
+                    // total internal reflection falls back to mirror reflection
+                    let transmitted_ray = match Renderer::create_refraction_ray(normal, &ray.direction, &hit_point, material.refractive_index, ray.time) {
+                        Some(refraction_ray) => refraction_ray,
+                        None => Renderer::create_reflection_ray(normal, &ray.direction, &hit_point, 0., ray.time)
+                    };
+
+                    let transmitted = Renderer::trace(transmitted_ray, scene, depth + 1, medium) * material.get_transmission_tint();
+                    pixel_color = pixel_color * material.opacity;
+                    pixel_color += transmitted * (1. - material.opacity);
+                    surface_weight *= material.opacity;
+                    specular = transmitted * (1. - material.opacity);
+                }
 
                 if material.reflectivity > 0. {
-                    let reflection_ray = Renderer::create_reflection_ray(&result_intersected_data.normal, &ray.direction, &hit_point);
-                    let reflection = Renderer::trace(reflection_ray, &scene, depth + 1) * material.reflectivity;
+                    let reflection_ray = Renderer::create_reflection_ray(&result_intersected_data.normal, &ray.direction, &hit_point, material.roughness, ray.time);
+                    let reflection = Renderer::trace(reflection_ray, scene, depth + 1, medium) * (material.specular_color / 255.) * material.reflectivity;
                     pixel_color = pixel_color * (1.0 - material.reflectivity);
                     pixel_color += reflection;
+                    surface_weight *= 1.0 - material.reflectivity;
+                    specular = specular * (1.0 - material.reflectivity) + &reflection;
+                }
+
+                if let Some(aovs) = aovs {
+                    Renderer::record_first_hit(&result_intersected_data, renderable, scene, aovs);
+                    aovs.diffuse_direct = diffuse_direct * surface_weight;
+                    aovs.diffuse_indirect = diffuse_indirect * surface_weight;
+                    aovs.emission = material.emissive_color * surface_weight;
+                    aovs.specular = specular;
                 }
             }
             None => {
-                pixel_color = *scene.get_background();
+                pixel_color = scene.get_background_color(&ray.direction);
+
+                if let Some(aovs) = aovs {
+                    Renderer::record_background(&ray, aovs);
+                    aovs.emission = pixel_color;
+                }
             }
         }
 
         pixel_color
     }
 
+    fn record_first_hit(intersection_data: &IntersectionData, renderable: &(dyn Renderable + Send), scene: &Scene, aovs: &mut AovSample) {
+        let material = renderable.get_material();
+        let mut albedo = material.get_diffuse_color(intersection_data.uv) / 255.;
+
+        if let Some(vertex_color) = intersection_data.vertex_color {
+            albedo = albedo * (vertex_color / 255.);
+        }
+
+        aovs.depth = intersection_data.distance;
+        aovs.normal = intersection_data.normal;
+        aovs.albedo = albedo;
+        aovs.object_id = scene.get_renderable_index(renderable).map_or(0., |index| (index + 1) as f32);
+        aovs.material_id = get_material_id(material);
+    }
+
+    // background counts as white surface facing the camera
+    fn record_background(ray: &Ray, aovs: &mut AovSample) {
+        aovs.depth = 0.;
+        aovs.normal = -Vector3::new(ray.direction.x, ray.direction.y, ray.direction.z);
+        aovs.albedo = Color::new(1., 1., 1.);
+    }
+
+    // geometric passes of camera ray for integrators that don't split their lighting
+    pub(crate) fn record_geometry(ray: &Ray, scene: &Scene, aovs: &mut AovSample) {
+        match Renderer::check_intersections(ray, scene) {
+            Some((intersection_data, renderable)) => Renderer::record_first_hit(&intersection_data, renderable, scene, aovs),
+            None => Renderer::record_background(ray, aovs)
+        }
+    }
+
+    fn store_aov_sample(render_scene: &mut RenderScene, pixel: usize, sample: &AovSample, frames: u32) {
+        if let Some(aovs) = &mut render_scene.aovs {
+            aovs.add_sample(pixel, sample, frames);
+        }
+
+        if let Some(guides) = &mut render_scene.guides {
+            let frames = frames as f32;
+            let average = |value: &mut f32, sample: f32| *value = (*value * frames + sample) / (frames + 1.);
+
+            for (c, value) in [sample.albedo.r, sample.albedo.g, sample.albedo.b].iter().enumerate() {
+                average(&mut guides.albedo[pixel * 3 + c], *value);
+            }
+
+            for (c, value) in [sample.normal.x, sample.normal.y, sample.normal.z].iter().enumerate() {
+                average(&mut guides.normal[pixel * 3 + c], *value);
+            }
+
+            average(&mut guides.depth[pixel], sample.depth);
+        }
+    }
+
+    fn is_camera_changed(&self) -> bool {
+        self.last_frame_camera_version != self.render_scene.camera.get_version()
+    }
+
+    // learned lighting no longer matches changed scene, its bounds or lights may differ too
+    fn rebuild_scene_caches(&mut self) {
+        if self.render_scene.scene.get_path_guide().is_some() {
+            self.set_path_guiding(true);
+        }
+
+        if self.render_scene.scene.get_light_reservoirs().is_some() {
+            self.set_resampled_lighting(true);
+        }
+    }
+
     pub fn render(&mut self) -> &Vec<f32> {
+        if self.last_frame_scene_version != self.render_scene.scene.get_version() {
+            self.rebuild_scene_caches();
+            self.reset();
+        } else if self.is_camera_changed() {
+            self.reset();
+        }
+
         let render_scene = Arc::get_mut(&mut self.render_scene).unwrap();
 
-        if self.last_frame_camera_position != render_scene.camera.position {
-            self.frames_total = 0;
-            self.last_frame_camera_position = render_scene.camera.position;
-            for i in 0..render_scene.image.len() { render_scene.image[i] = 0.; }
-        }
+        render_scene.scene.update_light_tree();
+        render_scene.integrator.prepare(&render_scene.scene);
+
+        // splatting integrators place samples anywhere on image so they always run at full resolution
+        let scale = if render_scene.integrator.is_splatting() { 1 } else { self.progressive_scale };
+        self.plan_pixel_samples(scale);
 
         let workers_num = self.thread_pool.get_workers_num() as u32;
         let height_per_thread = self.height / workers_num;
+        let frame_splats: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
 
         for i in 0..workers_num {
             let start_height = height_per_thread * i;
-            let end_height = start_height + height_per_thread;
+            let end_height = if i + 1 == workers_num { self.height } else { start_height + height_per_thread };
 
             let width = self.width.clone();
             let height = self.height.clone();
-            let frames_total = self.frames_total.clone();
             let mut render_scene_thread = Arc::clone(&self.render_scene);
+            let frame_splats_thread = Arc::clone(&frame_splats);
 
             let task = move || {
+                let sample_count = ((end_height - start_height) * width) as usize;
+
+                let needs_aovs = render_scene_thread.guides.is_some() || render_scene_thread.aovs.is_some();
+
+                // splatting integrators don't see camera rays of pixels so their passes are traced here
+                if needs_aovs && render_scene_thread.integrator.is_splatting() {
+                    for h in start_height..end_height {
+                        for w in 0..width {
+                            let pixel = (h * width + w) as usize;
+
+                            if render_scene_thread.pixel_samples[pixel] == 0 {
+                                continue;
+                            }
+
+                            let mut aovs = AovSample::new();
+                            let camera_ray = render_scene_thread.camera.get_camera_ray(w, h, width, height);
+                            Renderer::record_geometry(&camera_ray, &render_scene_thread.scene, &mut aovs);
+
+                            unsafe {
+                                let count = render_scene_thread.sample_counts[pixel];
+                                Renderer::store_aov_sample(Arc::get_mut_unchecked(&mut render_scene_thread), pixel, &aovs, count);
+                            }
+                        }
+                    }
+                }
+
+                if let Some(splats) = render_scene_thread.integrator.render_samples(&render_scene_thread.camera, &render_scene_thread.scene, width, height, sample_count) {
+                    let mut frame_splats = frame_splats_thread.lock().unwrap();
+
+                    if frame_splats.is_empty() {
+                        *frame_splats = splats;
+                    } else {
+                        for i in 0..splats.len() { frame_splats[i] += splats[i]; }
+                    }
+
+                    return;
+                }
+
+                let mut sampler = RandomSampler::new();
+
                 for h in start_height..end_height {
                     for w in 0..width {
-                        let offset = (h * width * 3 + w * 3) as usize;
-                        let camera_ray = render_scene_thread.camera.get_camera_ray(w, h, width, height);
-                        let rendered_color = Renderer::trace(camera_ray, &render_scene_thread.scene, 0);
+                        let pixel = (h * width + w) as usize;
+                        let offset = pixel * 3;
+
+                        for _ in 0..render_scene_thread.pixel_samples[pixel] {
+                            let camera_ray = render_scene_thread.camera.get_camera_ray(w, h, width, height);
+                            let mut aovs = AovSample::new();
 
-                        let _image = &render_scene_thread.image;
+                            let rendered_color = if needs_aovs {
+                                render_scene_thread.integrator.get_radiance_with_aovs(camera_ray, &render_scene_thread.scene, &mut sampler, &mut aovs)
+                            } else {
+                                render_scene_thread.integrator.get_radiance(camera_ray, &render_scene_thread.scene, &mut sampler)
+                            };
 
-                        let frames = frames_total as f32 / (frames_total + 1) as f32;
+                            let _image = &render_scene_thread.image;
+                            let count = render_scene_thread.sample_counts[pixel];
 
-                        let r = _image[offset] * frames + (rendered_color.r / (frames_total + 1) as f32);
-                        let g = _image[offset + 1] * frames + (rendered_color.g / (frames_total + 1) as f32);
-                        let b = _image[offset + 2] * frames + (rendered_color.b / (frames_total + 1) as f32);
+                            let frames = count as f32 / (count + 1) as f32;
 
-                        unsafe {
-                            let image = &mut Arc::get_mut_unchecked(&mut render_scene_thread).image;
+                            let r = _image[offset] * frames + (rendered_color.r / (count + 1) as f32);
+                            let g = _image[offset + 1] * frames + (rendered_color.g / (count + 1) as f32);
+                            let b = _image[offset + 2] * frames + (rendered_color.b / (count + 1) as f32);
 
-                            image[offset] = r;
-                            image[offset + 1] = g;
-                            image[offset + 2] = b;
+                            let luminance = rendered_color.get_luminance();
+                            let luminance_square = render_scene_thread.luminance_squares[pixel] * frames + luminance * luminance / (count + 1) as f32;
+
+                            unsafe {
+                                let render_scene = Arc::get_mut_unchecked(&mut render_scene_thread);
+
+                                render_scene.image[offset] = r;
+                                render_scene.image[offset + 1] = g;
+                                render_scene.image[offset + 2] = b;
+                                render_scene.luminance_squares[pixel] = luminance_square;
+
+                                if needs_aovs {
+                                    Renderer::store_aov_sample(render_scene, pixel, &aovs, count);
+                                }
+
+                                render_scene.sample_counts[pixel] += 1;
+                            }
                         }
                     }
                 }
@@ -248,9 +1073,53 @@ impl Renderer {
             self.thread_pool.add_task(Box::new(task));
         }
 
-        self.frames_total += 1;
         self.thread_pool.wait_all();
 
-        return &self.render_scene.image;
+        let scene = &mut Arc::get_mut(&mut self.render_scene).unwrap().scene;
+
+        if let Some(path_guide) = scene.get_path_guide_mut() {
+            path_guide.update();
+        }
+
+        if let Some(light_reservoirs) = scene.get_light_reservoirs_mut() {
+            light_reservoirs.update();
+        }
+
+        // splatting integrators deliver whole frame at once
+        let splats = frame_splats.lock().unwrap();
+
+        if !splats.is_empty() {
+            let render_scene = Arc::get_mut(&mut self.render_scene).unwrap();
+
+            for pixel in 0..render_scene.sample_counts.len() {
+                if render_scene.pixel_samples[pixel] == 0 {
+                    continue;
+                }
+
+                let frames = render_scene.sample_counts[pixel] as f32;
+
+                for i in pixel * 3..pixel * 3 + 3 {
+                    render_scene.image[i] = (render_scene.image[i] * frames + splats[i]) / (frames + 1.);
+                }
+
+                // every frame of splats is one estimate of the pixel for convergence check
+                let luminance = Color::new(splats[pixel * 3], splats[pixel * 3 + 1], splats[pixel * 3 + 2]).get_luminance();
+                render_scene.luminance_squares[pixel] = (render_scene.luminance_squares[pixel] * frames + luminance * luminance) / (frames + 1.);
+
+                render_scene.sample_counts[pixel] += 1;
+            }
+        }
+
+        self.frames_total += 1;
+        self.preview_scale = scale;
+
+        if scale > 1 {
+            self.update_preview(scale);
+            self.progressive_scale = scale / 2;
+        } else if let (Some(denoiser), Some(guides)) = (&self.denoiser, &self.render_scene.guides) {
+            self.denoised_image = denoiser.denoise(&self.render_scene.image, guides, self.width, self.height, self.frames_total);
+        }
+
+        self.get_image()
     }
 }
@@ -0,0 +1,116 @@
+use crate::math::vec3::Vector3;
+use std::sync::Mutex;
+
+const GRID_SIZE: usize = 1 << 16;
+
+// weighted reservoir holding one light picked out of count candidates
+#[derive(Clone, Copy, Debug)]
+pub struct Reservoir {
+    pub light: usize,
+    pub weight_sum: f32,
+    pub count: f32,
+    pub target: f32,
+}
+
+impl Reservoir {
+    pub fn new() -> Self {
+        Reservoir {
+            light: 0,
+            weight_sum: 0.,
+            count: 0.,
+            target: 0.
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weight_sum <= 0.
+    }
+
+    pub fn update(&mut self, light: usize, weight: f32, target: f32, count: f32, sample: f32) {
+        self.weight_sum += weight;
+        self.count += count;
+
+        if weight > 0. && sample * self.weight_sum < weight {
+            self.light = light;
+            self.target = target;
+        }
+    }
+
+    // unbiased contribution weight of the kept light
+    pub fn get_weight(&self) -> f32 {
+        if self.count > 0. && self.target > 0. { self.weight_sum / (self.count * self.target) } else { 0. }
+    }
+}
+
+impl Default for Reservoir {
+    fn default() -> Self {
+        Reservoir::new()
+    }
+}
+
+// reservoirs of previous frame stored in world space hash grid, every cell is keyed by position
+// and dominant normal axis so that shading points on the same surface reuse each other's lights
+pub struct ReservoirGrid {
+    cell_size: f32,
+    current: Vec<Reservoir>,
+    // reservoir kept in cell this frame with number of reservoirs stored there
+    next: Vec<Mutex<(Reservoir, f32)>>,
+}
+
+impl ReservoirGrid {
+    pub fn new(cell_size: f32) -> Self {
+        ReservoirGrid {
+            cell_size,
+            current: vec![Reservoir::new(); GRID_SIZE],
+            next: (0..GRID_SIZE).map(|_| Mutex::new((Reservoir::new(), 0.))).collect()
+        }
+    }
+
+    fn get_index(&self, point: &Vector3<f32>, normal: &Vector3<f32>) -> usize {
+        let x = f32::floor(point.x / self.cell_size) as i64;
+        let y = f32::floor(point.y / self.cell_size) as i64;
+        let z = f32::floor(point.z / self.cell_size) as i64;
+
+        let (ax, ay, az) = (normal.x.abs(), normal.y.abs(), normal.z.abs());
+        let axis = if ax >= ay && ax >= az { 0 } else if ay >= az { 2 } else { 4 };
+        let component = [normal.x, normal.y, normal.z][axis / 2];
+        let orientation = axis + if component < 0. { 1 } else { 0 };
+
+        let hash = (x.wrapping_mul(73856093) ^ y.wrapping_mul(19349663) ^ z.wrapping_mul(83492791) ^ (orientation as i64).wrapping_mul(2654435761)) as u64;
+
+        (hash % GRID_SIZE as u64) as usize
+    }
+
+    pub fn get(&self, point: &Vector3<f32>, normal: &Vector3<f32>) -> Reservoir {
+        self.current[self.get_index(point, normal)]
+    }
+
+    // one of reservoirs stored in cell is kept uniformly for next frame, merging them would mix
+    // weights of different shading points and bias reused lights
+    pub fn store(&self, point: &Vector3<f32>, normal: &Vector3<f32>, reservoir: &Reservoir, sample: f32) {
+        if reservoir.is_empty() {
+            return;
+        }
+
+        let mut next = self.next[self.get_index(point, normal)].lock().unwrap();
+        next.1 += 1.;
+
+        if sample * next.1 < 1. {
+            next.0 = *reservoir;
+        }
+    }
+
+    // cells that were not visited last frame keep their older reservoir
+    pub fn update(&mut self) {
+        for (current, next) in self.current.iter_mut().zip(self.next.iter_mut()) {
+            let (reservoir, stored) = next.get_mut().unwrap();
+
+            if *stored > 0. {
+                *current = *reservoir;
+            }
+
+            *reservoir = Reservoir::new();
+            *stored = 0.;
+        }
+    }
+}
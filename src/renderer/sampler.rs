@@ -0,0 +1,152 @@
+use std::f32::consts::PI;
+
+pub trait Sampler {
+    fn next_1d(&mut self) -> f32;
+
+    fn next_2d(&mut self) -> (f32, f32) {
+        (self.next_1d(), self.next_1d())
+    }
+
+    // following dimensions are taken from separate stream so that mutating one part of a path
+    // does not shift random numbers used by the others
+    fn start_stream(&mut self, _index: usize) {}
+}
+
+pub struct RandomSampler;
+
+impl RandomSampler {
+    pub fn new() -> Self {
+        RandomSampler
+    }
+}
+
+impl Default for RandomSampler {
+    fn default() -> Self {
+        RandomSampler::new()
+    }
+}
+
+impl Sampler for RandomSampler {
+    fn next_1d(&mut self) -> f32 {
+        rand::random()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PrimarySample {
+    value: f32,
+    last_modified: usize,
+    value_backup: f32,
+    modified_backup: usize,
+}
+
+// primary sample space sampler, every dimension is lazily mutated with gaussian small steps
+// or replaced by large steps and restored when proposal is rejected
+pub struct MetropolisSampler {
+    samples: Vec<PrimarySample>,
+    sigma: f32,
+    large_step_probability: f32,
+    stream_count: usize,
+    stream_index: usize,
+    sample_index: usize,
+    iteration: usize,
+    large_step: bool,
+    last_large_step_iteration: usize,
+}
+
+impl MetropolisSampler {
+    pub fn new(sigma: f32, large_step_probability: f32, stream_count: usize) -> Self {
+        MetropolisSampler {
+            samples: Vec::new(),
+            sigma,
+            large_step_probability,
+            stream_count,
+            stream_index: 0,
+            sample_index: 0,
+            iteration: 0,
+            large_step: true,
+            last_large_step_iteration: 0
+        }
+    }
+
+    pub fn is_large_step(&self) -> bool {
+        self.large_step
+    }
+
+    pub fn start_iteration(&mut self) {
+        self.iteration += 1;
+        self.large_step = rand::random::<f32>() < self.large_step_probability;
+        self.stream_index = 0;
+        self.sample_index = 0;
+    }
+
+    pub fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step_iteration = self.iteration;
+        }
+    }
+
+    pub fn reject(&mut self) {
+        for sample in self.samples.iter_mut() {
+            if sample.last_modified == self.iteration {
+                sample.value = sample.value_backup;
+                sample.last_modified = sample.modified_backup;
+            }
+        }
+
+        self.iteration -= 1;
+    }
+
+    fn get_normal() -> f32 {
+        let r1 = f32::max(rand::random::<f32>(), f32::MIN_POSITIVE);
+        let r2 = rand::random::<f32>();
+
+        f32::sqrt(-2. * f32::ln(r1)) * f32::cos(2. * PI * r2)
+    }
+
+    fn ensure_ready(&mut self, index: usize) {
+        if index >= self.samples.len() {
+            self.samples.resize(index + 1, PrimarySample { value: 0., last_modified: 0, value_backup: 0., modified_backup: 0 });
+        }
+
+        let iteration = self.iteration;
+        let sample = &mut self.samples[index];
+
+        // samples untouched since last accepted large step are outdated
+        if sample.last_modified < self.last_large_step_iteration {
+            sample.value = rand::random();
+            sample.last_modified = self.last_large_step_iteration;
+        }
+
+        sample.value_backup = sample.value;
+        sample.modified_backup = sample.last_modified;
+
+        if self.large_step {
+            sample.value = rand::random();
+        } else {
+            let small_steps = (iteration - sample.last_modified) as f32;
+            sample.value += MetropolisSampler::get_normal() * self.sigma * f32::sqrt(small_steps);
+            sample.value = f32::min(sample.value - f32::floor(sample.value), 0.99999994);
+        }
+
+        sample.last_modified = iteration;
+    }
+}
+
+impl Sampler for MetropolisSampler {
+    fn next_1d(&mut self) -> f32 {
+        let index = self.stream_index + self.stream_count * self.sample_index;
+        self.sample_index += 1;
+
+        if index >= self.samples.len() || self.samples[index].last_modified < self.iteration || self.iteration == 0 {
+            self.ensure_ready(index);
+        }
+
+        self.samples[index].value
+    }
+
+    fn start_stream(&mut self, index: usize) {
+        self.stream_index = index;
+        self.sample_index = 0;
+    }
+}
@@ -1,45 +1,276 @@
-use crate::renderables::renderable::Renderable;
+use crate::renderables::renderable::{Renderable, IntersectionData};
+use crate::math::ray::Ray;
 use crate::renderer::light::Light;
+use crate::renderer::sky::Sky;
+use crate::renderer::node::Node;
+use crate::renderer::camera::Camera;
+use crate::renderer::medium::Medium;
+use crate::renderer::path_guiding::PathGuide;
+use crate::renderer::light_tree::LightTree;
+use crate::renderer::reservoir::ReservoirGrid;
+use crate::renderables::instance::Instance;
+use crate::renderables::mesh::{Mesh, MeshOptions};
+use crate::renderables::material::Material;
+use crate::math::mat4::Matrix4;
 use crate::math::color::Color;
+use crate::math::vec3::Vector3;
+use crate::math::bbox::BBox;
 use crate::gl::obj_loader::load_obj;
+use crate::gl::gltf_loader::load_gltf_with_options;
+use crate::gl::ply_loader::load_ply;
+use crate::gl::stl_loader::load_stl;
+use std::path::Path;
+use crate::error::Result;
+use std::sync::Arc;
+
+pub enum Background {
+    Color(Color),
+    Sky(Sky)
+}
 
 pub struct Scene {
-    background: Color,
+    background: Background,
     lights: Vec<Light>,
-    renderables: Vec<Box<dyn Renderable + Send + 'static>>
+    // None when lights changed since tree was built
+    light_tree: Option<LightTree>,
+    light_reservoirs: Option<ReservoirGrid>,
+    cameras: Vec<Camera>,
+    renderables: Vec<Box<dyn Renderable + Send + 'static>>,
+    medium: Option<Medium>,
+    path_guide: Option<PathGuide>,
+    time: f32,
+    // incremented by every change affecting rendered image
+    version: u32
 }
 
 impl Scene {
     pub fn new(background: Color) -> Self {
       Scene {
-          background,
+          background: Background::Color(background),
           lights: Vec::new(),
+          light_tree: None,
+          light_reservoirs: None,
+          cameras: Vec::new(),
           renderables: Vec::new(),
+          medium: None,
+          path_guide: None,
+          time: 0.,
+          version: 0
       }
     }
 
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    fn mark_changed(&mut self) {
+        self.version = self.version.wrapping_add(1);
+    }
+
+    pub fn get_time(&self) -> f32 {
+        self.time
+    }
+
+    // animated renderables follow ray time, lights are evaluated once per frame here
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+
+        for light in &mut self.lights {
+            light.set_time(time);
+        }
+
+        self.light_tree = None;
+        self.mark_changed();
+    }
+
     pub fn get_renderables(&self) -> &Vec<Box<dyn Renderable + Send + 'static >> {
         &self.renderables
     }
 
+    // position of renderable returned by intersect
+    pub fn get_renderable_index(&self, renderable: &(dyn Renderable + Send)) -> Option<usize> {
+        self.renderables.iter().position(|item| std::ptr::addr_eq(item.as_ref(), renderable))
+    }
+
+    // nearest intersection among all renderables
+    pub fn intersect(&self, ray: &Ray) -> Option<(IntersectionData, &(dyn Renderable + Send))> {
+        let mut near = f32::INFINITY;
+        let mut result = None;
+
+        for renderable in &self.renderables {
+            if let Some(intersection_data) = renderable.intersects(ray) {
+                if intersection_data.distance < near {
+                    near = intersection_data.distance;
+                    result = Some((intersection_data, renderable.as_ref()));
+                }
+            }
+        }
+
+        result
+    }
+
     pub fn get_lights(&self) -> &Vec<Light>{
         &self.lights
     }
 
-    pub fn add_light(&mut self, light: Light) {self.lights.push(light)}
+    pub fn add_light(&mut self, light: Light) {
+        self.lights.push(light);
+        self.light_tree = None;
+        self.mark_changed();
+    }
+
+    pub fn set_light(&mut self, index: usize, light: Light) {
+        self.lights[index] = light;
+        self.light_tree = None;
+        self.mark_changed();
+    }
+
+    // tree is rebuilt once after any number of light changes
+    pub fn update_light_tree(&mut self) {
+        if self.light_tree.is_none() {
+            self.light_tree = Some(LightTree::new(&self.lights));
+        }
+    }
+
+    pub fn get_light_tree(&self) -> Option<&LightTree> {
+        self.light_tree.as_ref()
+    }
+
+    // reservoirs reused by resampled direct lighting, None samples every light
+    pub fn set_light_reservoirs(&mut self, light_reservoirs: Option<ReservoirGrid>) {
+        self.light_reservoirs = light_reservoirs;
+    }
+
+    pub fn get_light_reservoirs(&self) -> Option<&ReservoirGrid> {
+        self.light_reservoirs.as_ref()
+    }
 
-    pub fn get_background(&self) -> &Color {
+    pub fn get_light_reservoirs_mut(&mut self) -> Option<&mut ReservoirGrid> {
+        self.light_reservoirs.as_mut()
+    }
+
+    pub fn get_background(&self) -> &Background {
         &self.background
     }
 
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+        self.mark_changed();
+    }
+
+    pub fn set_sky(&mut self, sky: Sky, sun_intensity: f32) {
+        self.add_light(sky.create_sun_light(sun_intensity));
+        self.background = Background::Sky(sky);
+        self.mark_changed();
+    }
+
+    pub fn get_background_color(&self, direction: &Vector3<f32>) -> Color {
+        match &self.background {
+            Background::Color(color) => *color,
+            Background::Sky(sky) => sky.get_radiance(direction)
+        }
+    }
+
+    // medium filling whole scene outside of closed media boundaries
+    pub fn set_medium(&mut self, medium: Option<Medium>) {
+        self.medium = medium;
+        self.mark_changed();
+    }
+
+    pub fn get_medium(&self) -> Option<&Medium> {
+        self.medium.as_ref()
+    }
+
+    // bounds of all bounded renderables, None when scene has only unbounded ones
+    pub fn get_bbox(&self) -> Option<BBox> {
+        self.renderables.iter()
+            .filter_map(|renderable| renderable.get_bbox())
+            .fold(None, |bounds: Option<BBox>, bbox| Some(bounds.map_or(bbox, |bounds| bounds.union(&bbox))))
+    }
+
+    pub fn set_path_guide(&mut self, path_guide: Option<PathGuide>) {
+        self.path_guide = path_guide;
+    }
+
+    pub fn get_path_guide(&self) -> Option<&PathGuide> {
+        self.path_guide.as_ref()
+    }
+
+    pub fn get_path_guide_mut(&mut self) -> Option<&mut PathGuide> {
+        self.path_guide.as_mut()
+    }
+
     pub fn add_renderable(&mut self, renderable: Box<dyn Renderable + Send + 'static>) {
         self.renderables.push(renderable);
+        self.mark_changed();
+    }
+
+    // any returned material counts as change, None for renderables sharing their material. Instances
+    // added from nodes get their own copy, so edit doesn't reach other instances of the same renderable
+    pub fn get_material_mut(&mut self, index: usize) -> Option<&mut Material> {
+        let material = self.renderables.get_mut(index).and_then(|renderable| renderable.get_material_mut());
+
+        if material.is_some() {
+            self.version = self.version.wrapping_add(1);
+        }
+
+        material
+    }
+
+    pub fn get_cameras(&self) -> &Vec<Camera> {
+        &self.cameras
+    }
+
+    pub fn add_camera(&mut self, camera: Camera) {self.cameras.push(camera)}
+
+    // flattens node hierarchy into world space renderables, lights and cameras
+    pub fn add_node(&mut self, node: &Node) {
+        node.traverse(&Matrix4::identity(), &mut |node, world| {
+            if let Some(renderable) = &node.renderable {
+                self.renderables.push(Box::new(Instance::new(Arc::clone(renderable), *world)));
+            }
+
+            if let Some(light) = &node.light {
+                self.lights.push(Node::get_world_light(light, world));
+            }
+
+            if let Some(camera) = &node.camera {
+                let mut camera = camera.clone();
+                camera.apply_matrix(world);
+                self.cameras.push(camera);
+            }
+        });
+
+        self.light_tree = None;
+        self.mark_changed();
+    }
+
+    pub fn load_model(&mut self, path: String) -> Result<()> {
+        self.load_model_with_options(path, &MeshOptions::new())
     }
 
-    pub fn load_model(&mut self, path: String) {
-        let meshes = load_obj(&path);
+    pub fn load_model_with_options(&mut self, path: String, options: &MeshOptions) -> Result<()> {
+        let extension = Path::new(&path).extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+
+        match extension.as_deref() {
+            Some("gltf") | Some("glb") => {
+                for node in load_gltf_with_options(&path, options)? {
+                    self.add_node(&node);
+                }
+            },
+            Some("ply") => self.add_meshes(load_ply(&path)?, options),
+            Some("stl") => self.add_meshes(load_stl(&path)?, options),
+            _ => self.add_meshes(load_obj(&path)?, options)
+        }
+
+        Ok(())
+    }
 
-        for mesh in meshes {
+    fn add_meshes(&mut self, meshes: Vec<Mesh>, options: &MeshOptions) {
+        for mut mesh in meshes {
+            mesh.process(options);
             self.add_renderable(Box::new(mesh));
         }
     }
@@ -0,0 +1,82 @@
+use crate::renderer::renderer::Renderer;
+use crate::error::{Error, Result};
+use std::path::Path;
+
+const DEFAULT_FRAME_RATE: f32 = 24.;
+const DEFAULT_SAMPLES_PER_FRAME: u32 = 16;
+
+// renders animation frame by frame without a window and writes numbered images
+pub struct SequenceRenderer {
+    renderer: Renderer,
+    frame_rate: f32,
+    samples_per_frame: u32,
+    shutter: f32,
+}
+
+impl SequenceRenderer {
+    pub fn new(renderer: Renderer) -> Self {
+        SequenceRenderer {
+            renderer,
+            frame_rate: DEFAULT_FRAME_RATE,
+            samples_per_frame: DEFAULT_SAMPLES_PER_FRAME,
+            shutter: 0.
+        }
+    }
+
+    pub fn set_frame_rate(&mut self, frame_rate: f32) {
+        self.frame_rate = frame_rate;
+    }
+
+    pub fn set_samples_per_frame(&mut self, samples_per_frame: u32) {
+        self.samples_per_frame = samples_per_frame;
+    }
+
+    // fraction of frame duration the shutter stays open, 0.5 matches 180 degree film shutter
+    pub fn set_shutter(&mut self, shutter: f32) {
+        self.shutter = shutter;
+    }
+
+    pub fn get_renderer(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    pub fn get_frame_time(&self, frame: u32) -> f32 {
+        frame as f32 / self.frame_rate
+    }
+
+    pub fn render_frame(&mut self, frame: u32) -> &Vec<f32> {
+        let time = self.get_frame_time(frame);
+        self.renderer.set_time(time, self.shutter / self.frame_rate);
+
+        // with adaptive sampling samples_per_frame only limits frames rendered before noise threshold is reached
+        self.renderer.render_until_converged(self.samples_per_frame);
+
+        self.renderer.get_image()
+    }
+
+    // writes frames start..=end into directory as frame_0001.png, returns written paths
+    pub fn render_sequence(&mut self, start_frame: u32, end_frame: u32, directory: &str) -> Result<Vec<String>> {
+        std::fs::create_dir_all(directory).map_err(|e| Error::io(directory, e))?;
+
+        let mut paths = Vec::new();
+
+        for frame in start_frame..=end_frame {
+            let path = Path::new(directory).join(format!("frame_{:04}.png", frame));
+            let path = path.to_string_lossy().to_string();
+
+            self.render_frame(frame);
+            save_image(&path, self.renderer.get_width(), self.renderer.get_height(), self.renderer.get_image())?;
+
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+}
+
+pub fn save_image(path: &str, width: u32, height: u32, image: &[f32]) -> Result<()> {
+    let pixels: Vec<u8> = image.iter().map(|value| value.clamp(0., 255.) as u8).collect();
+
+    image::save_buffer(path, &pixels, width, height, image::ColorType::Rgb8)
+        .map_err(|e| Error::image(path, e.to_string()))
+}
@@ -0,0 +1,159 @@
+use crate::math::vec3::Vector3;
+use crate::math::color::Color;
+use crate::renderer::light::Light;
+use std::f32::consts::PI;
+
+// angular radius of the sun disk as seen from earth, in radians
+pub const SUN_ANGULAR_RADIUS: f32 = 0.00465;
+const SKY_EXPOSURE: f32 = 0.04;
+
+// Preetham analytic sky model, "A Practical Analytic Model for Daylight" (1999)
+#[derive(Clone, Debug)]
+pub struct Sky {
+    sun_direction: Vector3<f32>,
+    turbidity: f32,
+    ground_albedo: f32,
+    sun_theta: f32,
+    zenith: [f32; 3],
+    perez_luminance: [f32; 5],
+    perez_x: [f32; 5],
+    perez_y: [f32; 5],
+}
+
+impl Sky {
+    pub fn new(sun_direction: Vector3<f32>, turbidity: f32, ground_albedo: f32) -> Self {
+        let mut sun_direction = sun_direction;
+        sun_direction.normalize();
+
+        let t = turbidity;
+        let sun_theta = f32::acos(sun_direction.y.clamp(-1., 1.));
+        let zenith_theta = f32::min(sun_theta, PI / 2.);
+
+        let chi = (4. / 9. - t / 120.) * (PI - 2. * zenith_theta);
+        let zenith_luminance = (4.0453 * t - 4.9710) * f32::tan(chi) - 0.2155 * t + 2.4192;
+
+        let theta = zenith_theta;
+        let theta2 = theta * theta;
+        let theta3 = theta2 * theta;
+
+        let zenith_x =
+            t * t * (0.00166 * theta3 - 0.00375 * theta2 + 0.00209 * theta) +
+            t * (-0.02903 * theta3 + 0.06377 * theta2 - 0.03202 * theta + 0.00394) +
+            (0.11693 * theta3 - 0.21196 * theta2 + 0.06052 * theta + 0.25886);
+
+        let zenith_y =
+            t * t * (0.00275 * theta3 - 0.00610 * theta2 + 0.00317 * theta) +
+            t * (-0.04214 * theta3 + 0.08970 * theta2 - 0.04153 * theta + 0.00516) +
+            (0.15346 * theta3 - 0.26756 * theta2 + 0.06670 * theta + 0.26688);
+
+        Sky {
+            sun_direction,
+            turbidity,
+            ground_albedo,
+            sun_theta: zenith_theta,
+            zenith: [f32::max(zenith_luminance, 0.), zenith_x, zenith_y],
+            perez_luminance: [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703
+            ],
+            perez_x: [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452
+            ],
+            perez_y: [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529
+            ],
+        }
+    }
+
+    pub fn get_sun_direction(&self) -> &Vector3<f32> {
+        &self.sun_direction
+    }
+
+    pub fn get_turbidity(&self) -> f32 {
+        self.turbidity
+    }
+
+    pub fn get_ground_albedo(&self) -> f32 {
+        self.ground_albedo
+    }
+
+    pub fn create_sun_light(&self, intensity: f32) -> Light {
+        let sun_color = self.get_radiance(&self.sun_direction);
+        let max_channel = f32::max(sun_color.r, f32::max(sun_color.g, sun_color.b));
+
+        let color = if max_channel > 0. {
+            sun_color * (255. / max_channel)
+        } else {
+            Color::new(255., 255., 255.)
+        };
+
+        Light::new_directional(color, intensity, self.sun_direction, SUN_ANGULAR_RADIUS)
+    }
+
+    fn perez(coefficients: &[f32; 5], cos_theta: f32, gamma: f32) -> f32 {
+        let [a, b, c, d, e] = *coefficients;
+        let cos_gamma = f32::cos(gamma);
+
+        (1. + a * f32::exp(b / cos_theta)) * (1. + c * f32::exp(d * gamma) + e * cos_gamma * cos_gamma)
+    }
+
+    fn sky_value(&self, coefficients: &[f32; 5], zenith: f32, cos_theta: f32, gamma: f32) -> f32 {
+        let at_direction = Sky::perez(coefficients, cos_theta, gamma);
+        let at_zenith = Sky::perez(coefficients, 1., self.sun_theta);
+
+        zenith * at_direction / at_zenith
+    }
+
+    fn xyy_to_rgb(luminance: f32, x: f32, y: f32) -> Color {
+        if y <= 0. {
+            return Color::new(0., 0., 0.);
+        }
+
+        let big_x = x / y * luminance;
+        let big_z = (1. - x - y) / y * luminance;
+
+        let r = 3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z;
+        let g = -0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z;
+        let b = 0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z;
+
+        Color::new(f32::max(r, 0.), f32::max(g, 0.), f32::max(b, 0.))
+    }
+
+    pub fn get_radiance(&self, direction: &Vector3<f32>) -> Color {
+        let mut view = *direction;
+        view.normalize();
+
+        let below_horizon = view.y < 0.;
+
+        // ground is approximated as a diffuse reflector of the mirrored sky
+        if below_horizon {
+            view.y = -view.y;
+        }
+
+        let cos_theta = f32::max(view.y, 0.01);
+        let gamma = f32::acos(view.dot(&self.sun_direction).clamp(-1., 1.));
+
+        let luminance = self.sky_value(&self.perez_luminance, self.zenith[0], cos_theta, gamma);
+        let x = self.sky_value(&self.perez_x, self.zenith[1], cos_theta, gamma);
+        let y = self.sky_value(&self.perez_y, self.zenith[2], cos_theta, gamma);
+
+        let radiance = Sky::xyy_to_rgb(luminance, x, y) * (255. * SKY_EXPOSURE);
+
+        if below_horizon {
+            return radiance * self.ground_albedo;
+        }
+
+        radiance
+    }
+}
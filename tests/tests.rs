@@ -8,6 +8,66 @@ mod tests {
     use pathtracer::math::mat4::Matrix4;
     use pathtracer::renderer::camera::Camera;
     use pathtracer::math::spherical::Spherical;
+    use pathtracer::renderer::sky::Sky;
+    use pathtracer::renderables::sphere::Sphere;
+    use pathtracer::renderables::instance::Instance;
+    use pathtracer::renderables::renderable::Renderable;
+    use pathtracer::math::ray::Ray;
+    use std::sync::Arc;
+    use pathtracer::math::quaternion::Quaternion;
+    use pathtracer::renderer::node::Node;
+    use pathtracer::renderer::scene::Scene;
+    use pathtracer::renderer::light::Light;
+    use pathtracer::gl::gltf_loader::load_gltf;
+    use pathtracer::gl::ply_loader::load_ply;
+    use pathtracer::gl::stl_loader::load_stl;
+    use pathtracer::gl::obj_loader::load_obj;
+    use pathtracer::error::Error;
+    use pathtracer::renderables::mesh::{Mesh, MeshOptions};
+    use pathtracer::renderables::cuboid::Cuboid;
+    use pathtracer::renderables::rectangle::Rectangle;
+    use pathtracer::renderables::disk::Disk;
+    use pathtracer::renderables::cylinder::Cylinder;
+    use pathtracer::renderables::cone::Cone;
+    use pathtracer::renderables::torus::Torus;
+    use pathtracer::renderables::capsule::Capsule;
+    use pathtracer::renderables::csg::Csg;
+    use pathtracer::renderables::sdf::{self, Sdf};
+    use pathtracer::math::bbox::BBox;
+    use pathtracer::math::solve_quadratic;
+    use pathtracer::math::animation::{Track, TransformTrack, Interpolation};
+    use pathtracer::renderer::light::LightAnimation;
+    use pathtracer::renderer::renderer::{Renderer, RenderRegion};
+    use pathtracer::renderer::sequence::SequenceRenderer;
+    use pathtracer::renderer::medium::{Medium, DensityGrid};
+    use pathtracer::renderables::material::Subsurface;
+    use pathtracer::renderer::integrator::{Integrator, PathIntegrator};
+    use pathtracer::renderer::bidirectional::BidirectionalIntegrator;
+    use pathtracer::renderer::bsdf::Bsdf;
+    use pathtracer::renderables::renderable::IntersectionData;
+    use pathtracer::renderer::photon_map::{Photon, PhotonMap};
+    use pathtracer::renderer::photon_mapping::PhotonMapIntegrator;
+    use pathtracer::renderables::plane::Plane;
+    use pathtracer::renderer::sampler::{Sampler, RandomSampler, MetropolisSampler};
+    use pathtracer::renderer::metropolis::MetropolisIntegrator;
+    use pathtracer::renderer::path_guiding::PathGuide;
+    use pathtracer::renderer::light_tree::LightTree;
+    use pathtracer::renderer::reservoir::Reservoir;
+    use pathtracer::renderer::denoiser::{Denoiser, GuideBuffers};
+    use pathtracer::renderer::aov::Aov;
+    use pathtracer::renderer::adaptive_sampling::AdaptiveSampling;
+
+    // unit sphere at origin lit by point light on +z axis
+    fn create_sphere_scene(material: Material, intensity: f32, light_distance: f32) -> Scene {
+        let mut scene = Scene::new(Color::new(0., 0., 0.));
+        scene.add_light(Light::new(Color::new(255., 255., 255.), intensity, Vector3::new(0., 0., light_distance)));
+        scene.add_renderable(Box::new(Sphere::new(1., Vector3::new(0., 0., 0.), material)));
+        scene
+    }
+
+    fn create_sphere_camera() -> Camera {
+        Camera::new(30., Vector3::new(0., 0., 5.), Vector3::new(0., 0., 0.))
+    }
 
     #[test]
     fn cross_product() {
@@ -136,4 +196,1135 @@ mod tests {
         assert_eq!(spherical.polar_angle, 1.0068536854342678);
         assert_eq!(spherical.azimuth_angle, 0.3217505543966422);
     }
+
+    #[test]
+    fn sky_radiance() {
+        let sky = Sky::new(Vector3::new(0., 1., 1.), 3., 0.3);
+
+        let towards_sun = sky.get_radiance(&Vector3::new(0., 1., 1.));
+        let away_from_sun = sky.get_radiance(&Vector3::new(0., 1., -1.));
+        let ground = sky.get_radiance(&Vector3::new(0., -1., -1.));
+
+        assert!(towards_sun.r + towards_sun.g + towards_sun.b > away_from_sun.r + away_from_sun.g + away_from_sun.b);
+        assert!(away_from_sun.b > away_from_sun.r);
+        assert!(ground.b < away_from_sun.b);
+    }
+
+    #[test]
+    fn instance_intersection() {
+        let sphere = Arc::new(Sphere::new(1., Vector3::new(0., 0., 0.), Material::new()));
+
+        let mut transform = Matrix4::identity();
+        transform.make_translation(0., 0., -10.);
+        let mut scale = Matrix4::identity();
+        scale.make_scale(2., 2., 2.);
+        transform.multiply(&scale);
+
+        let instance = Instance::new(sphere, transform);
+        let ray = Ray::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., -1.));
+
+        let intersection = instance.intersects(&ray).unwrap();
+        assert!((intersection.distance - 8.).abs() < 0.0001);
+        assert!((intersection.normal.z - 1.).abs() < 0.0001);
+    }
+
+    #[test]
+    fn scene_graph_flatten() {
+        let mut parent = Node::new(String::from("parent"));
+        parent.translation = Vector3::new(0., 0., -10.);
+        parent.rotation = Quaternion::from_axis_angle(&Vector3::new(0., 1., 0.), std::f32::consts::PI / 2.);
+
+        let mut child = Node::new(String::from("child"));
+        child.translation = Vector3::new(1., 0., 0.);
+        child.light = Some(Light::new(Color::new(255., 255., 255.), 1., Vector3::new(0., 0., 0.)));
+        parent.add_child(child);
+
+        let mut scene = Scene::new(Color::new(0., 0., 0.));
+        scene.add_node(&parent);
+
+        let position = scene.get_lights()[0].position;
+        assert!(position.x.abs() < 0.0001);
+        assert!((position.z + 11.).abs() < 0.0001);
+    }
+
+    #[test]
+    fn gltf_import() {
+        let gltf = r#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"nodes": [0]}],
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": {"KHR_lights_punctual": {"lights": [{"type": "point", "color": [1, 1, 1], "intensity": 5}]}},
+            "nodes": [
+                {"name": "root", "translation": [0, 0, -5], "children": [1, 2, 3]},
+                {"mesh": 0},
+                {"camera": 0, "translation": [1, 2, 3], "rotation": [0, 0, 0.7071068, 0.7071068]},
+                {"translation": [0, 4, 0], "extensions": {"KHR_lights_punctual": {"light": 0}}}
+            ],
+            "cameras": [{"type": "perspective", "perspective": {"yfov": 1, "znear": 0.1}}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2}, "material": 0}]}],
+            "materials": [{"pbrMetallicRoughness": {"baseColorFactor": [1, 0, 0, 1], "baseColorTexture": {"index": 0}, "metallicFactor": 0}}],
+            "textures": [{"source": 0}],
+            "images": [{"uri": "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAIAAACQd1PeAAAADElEQVR4nGNoYGAAAAGEAIH5/mWIAAAAAElFTkSuQmCC"}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]},
+                {"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3"},
+                {"bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2"}
+            ],
+            "bufferViews": [{"buffer": 0, "byteLength": 36}, {"buffer": 0, "byteOffset": 36, "byteLength": 36}, {"buffer": 0, "byteOffset": 72, "byteLength": 24}],
+            "buffers": [{"byteLength": 96, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAmpkZPwAAAADNzEw/mpkZPwAAAADNzEw/mpkZPwAAAADNzEw/AAAAPwAAAD8AAAA/AAAAPwAAAD8AAAA/"}]
+        }"#;
+
+        let path = std::env::temp_dir().join("pathtracer_gltf_import.gltf");
+        std::fs::write(&path, gltf).unwrap();
+
+        let nodes = load_gltf(path.to_str().unwrap()).unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].name, "root");
+
+        let mut scene = Scene::new(Color::new(0., 0., 0.));
+        scene.add_node(&nodes[0]);
+        assert_eq!(scene.get_renderables().len(), 1);
+
+        let renderable = &scene.get_renderables()[0];
+        assert_eq!(renderable.get_material().diffuse_color, Color::new(255., 0., 0.));
+        assert_eq!(renderable.get_material().get_diffuse_color((0.5, 0.5)), Color::new(128., 0., 0.));
+
+        // vertex normals are used for shading
+        let ray = Ray::new(Vector3::new(0.25, 0.25, 0.), Vector3::new(0., 0., -1.));
+        let intersection = renderable.intersects(&ray).unwrap();
+        assert!((intersection.distance - 5.).abs() < 0.0001);
+        assert!((&intersection.normal - &Vector3::new(0.6, 0., 0.8)).magnitude() < 0.0001);
+
+        assert_eq!(scene.get_lights().len(), 1);
+        assert!((&scene.get_lights()[0].position - &Vector3::new(0., 4., -5.)).magnitude() < 0.0001);
+        assert_eq!(scene.get_lights()[0].intensity, 5.);
+
+        // camera rolled around its view direction keeps looking down -z
+        let camera = &scene.get_cameras()[0];
+        assert!((&camera.get_position() - &Vector3::new(1., 2., -2.)).magnitude() < 0.0001);
+        assert!((&camera.get_target() - &Vector3::new(1., 2., -3.)).magnitude() < 0.0001);
+        assert!((&camera.get_up() - &Vector3::new(-1., 0., 0.)).magnitude() < 0.0001);
+
+        // unit square as strip and fan, lines have no surface and indices have to stay inside vertices
+        let primitives = r#"{
+            "asset": {"version": "2.0"},
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"mesh": 0}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": INDICES, "mode": MODE}]}],
+            "accessors": [
+                {"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]},
+                {"bufferView": 1, "componentType": 5123, "count": 4, "type": "SCALAR"},
+                {"bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR"}
+            ],
+            "bufferViews": [{"buffer": 0, "byteLength": 48}, {"buffer": 0, "byteOffset": 48, "byteLength": 8}, {"buffer": 0, "byteOffset": 56, "byteLength": 8}],
+            "buffers": [{"byteLength": 64, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAACAPwAAgD8AAAAAAAABAAIAAwAAAAEABwAAAA=="}]
+        }"#;
+
+        let load = |mode: &str, indices: &str| {
+            std::fs::write(&path, primitives.replace("MODE", mode).replace("INDICES", indices)).unwrap();
+            load_gltf(path.to_str().unwrap()).map(|nodes| {
+                let mut scene = Scene::new(Color::new(0., 0., 0.));
+                scene.add_node(&nodes[0]);
+                scene.get_renderables().iter().map(|renderable| renderable.sample_surface(0.5, 0.5, 0.).unwrap().2).collect::<Vec<f32>>()
+            })
+        };
+
+        assert_eq!(load("5", "1").unwrap(), vec![1.]);
+        assert_eq!(load("6", "1").unwrap(), vec![1.]);
+        assert!(load("1", "1").unwrap().is_empty());
+
+        match load("4", "2") {
+            Err(Error::Parse { .. }) => {},
+            _ => panic!("out of range index should fail")
+        }
+    }
+
+    #[test]
+    fn ply_import() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+            property uchar red\nproperty uchar green\nproperty uchar blue\nelement face 1\n\
+            property list uchar int vertex_indices\nend_header\n\
+            0 0 0 255 0 0\n1 0 0 255 0 0\n1 1 0 255 0 0\n0 1 0 255 0 0\n4 0 1 2 3\n";
+
+        let path = std::env::temp_dir().join("pathtracer_ply_import.ply");
+        std::fs::write(&path, ply).unwrap();
+
+        let meshes = load_ply(path.to_str().unwrap()).unwrap();
+        let ray = Ray::new(Vector3::new(0.25, 0.75, 1.), Vector3::new(0., 0., -1.));
+        let intersection = meshes[0].intersects(&ray).unwrap();
+
+        assert!((intersection.distance - 1.).abs() < 0.0001);
+        assert_eq!(intersection.vertex_color, Some(Color::new(255., 0., 0.)));
+    }
+
+    #[test]
+    fn stl_import() {
+        let mut stl = vec![0u8; 80];
+        stl.extend_from_slice(&1u32.to_le_bytes());
+        for value in &[0f32, 0., 1., 0., 0., 0., 1., 0., 0., 0., 1., 0.] {
+            stl.extend_from_slice(&value.to_le_bytes());
+        }
+        stl.extend_from_slice(&[0, 0]);
+
+        let path = std::env::temp_dir().join("pathtracer_stl_import.stl");
+        std::fs::write(&path, stl).unwrap();
+
+        let meshes = load_stl(path.to_str().unwrap()).unwrap();
+        let ray = Ray::new(Vector3::new(0.25, 0.25, 2.), Vector3::new(0., 0., -1.));
+
+        assert!((meshes[0].intersects(&ray).unwrap().distance - 2.).abs() < 0.0001);
+
+        // truncated binary file is not read as ascii
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 10]).unwrap();
+        assert!(matches!(load_stl(path.to_str().unwrap()), Err(Error::Parse { .. })));
+
+        std::fs::write(&path, "solid empty\nendsolid empty\n").unwrap();
+        assert!(matches!(load_stl(path.to_str().unwrap()), Err(Error::Parse { .. })));
+
+        let ascii = "solid quad\nfacet normal 0 0 1\nouter loop\nvertex 0 0 1\nvertex 1 0 1\nvertex 0 1 1\nendloop\nendfacet\nendsolid quad\n";
+        std::fs::write(&path, ascii).unwrap();
+        assert!((load_stl(path.to_str().unwrap()).unwrap()[0].intersects(&ray).unwrap().distance - 1.).abs() < 0.0001);
+    }
+
+    #[test]
+    fn model_loading_errors() {
+        match load_obj("./assets/missing.obj") {
+            Err(Error::Io { path, .. }) => assert_eq!(path, "./assets/missing.obj"),
+            _ => panic!("expected io error")
+        }
+
+        let ply = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\nend_header\n0 zero 0\n";
+        let path = std::env::temp_dir().join("pathtracer_broken.ply");
+        std::fs::write(&path, ply).unwrap();
+
+        match load_ply(path.to_str().unwrap()) {
+            Err(Error::Parse { line, .. }) => assert_eq!(line, Some(8)),
+            _ => panic!("expected parse error")
+        }
+
+        // list length from the file is not trusted for allocation
+        let mut ply = b"ply\nformat binary_little_endian 1.0\nelement face 1\nproperty list uint int vertex_indices\nend_header\n".to_vec();
+        ply.extend_from_slice(&u32::MAX.to_le_bytes());
+        let path = std::env::temp_dir().join("pathtracer_huge_list.ply");
+        std::fs::write(&path, ply).unwrap();
+
+        match load_ply(path.to_str().unwrap()) {
+            Err(Error::Parse { .. }) => {},
+            _ => panic!("expected parse error")
+        }
+
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let path = std::env::temp_dir().join("pathtracer_no_material.obj");
+        std::fs::write(&path, obj).unwrap();
+
+        let meshes = load_obj(path.to_str().unwrap()).unwrap();
+        assert_eq!(meshes[0].get_material().diffuse_color, Material::new().diffuse_color);
+    }
+
+    #[test]
+    fn mtl_material_mapping() {
+        let meshes = load_obj("./assets/cornell_box/CornellBox-Water.obj").unwrap();
+        let water = meshes.iter().find(|mesh| mesh.get_name() == "water").unwrap().get_material();
+
+        assert_eq!(water.illumination_model, 7);
+        assert_eq!(water.refractive_index, 1.33);
+        assert_eq!(water.opacity, 0.);
+        assert!((water.reflectivity - 0.3).abs() < 0.0001);
+
+        let meshes = load_obj("./assets/cornell_box/CornellBox-Glossy.obj").unwrap();
+        let light = meshes.iter().find(|mesh| mesh.get_name() == "light").unwrap().get_material();
+
+        assert_eq!(light.emissive_color, Color::new(255., 255., 255.));
+    }
+
+    #[test]
+    fn mesh_processing() {
+        let corners = [
+            Vector3::new(0., 0., 0.), Vector3::new(2., 0., 0.), Vector3::new(2., 2., 0.), Vector3::new(0., 2., 0.),
+            Vector3::new(0., 0., 2.), Vector3::new(2., 0., 2.), Vector3::new(2., 2., 2.), Vector3::new(0., 2., 2.)
+        ];
+        let faces = [
+            [0, 2, 1], [0, 3, 2], [4, 5, 6], [4, 6, 7], [0, 1, 5], [0, 5, 4],
+            [3, 6, 2], [3, 7, 6], [0, 4, 7], [0, 7, 3], [1, 2, 6], [1, 6, 5]
+        ];
+
+        let mut triangles: Vec<Triangle> = faces.iter()
+            .map(|f| Triangle::new(corners[f[0]], corners[f[1]], corners[f[2]]))
+            .collect();
+        triangles.push(Triangle::new(corners[0], corners[0], corners[1]));
+
+        let mut mesh = Mesh::new(Material::new(), triangles, String::from("cube"));
+
+        let mut options = MeshOptions::new();
+        options.remove_degenerate = true;
+        options.recenter = true;
+        options.scale_to_unit = true;
+        options.smooth_normals_angle = Some(30.);
+        mesh.process(&options);
+
+        assert_eq!(mesh.get_triangles().len(), 12);
+        assert!((mesh.get_surface_area() - 6.).abs() < 0.0001);
+        assert!((mesh.get_volume() - 1.).abs() < 0.0001);
+        assert!((mesh.get_bbox().min.x + 0.5).abs() < 0.0001);
+        assert!((mesh.get_bbox().max.y - 0.5).abs() < 0.0001);
+
+        // close vertices on both sides of grid cell boundary are welded, distant ones are kept
+        let create_mesh = || Mesh::new(Material::new(), vec![
+            Triangle::with_uvs(Vector3::new(0., 0., 0.), Vector3::new(0.0049, 1., 0.), Vector3::new(0., 1., 1.), [(0., 0.), (1., 0.), (0., 1.)]),
+            Triangle::new(Vector3::new(0.0051, 1., 0.), Vector3::new(1., 1., 0.), Vector3::new(0.03, 0., 0.))
+        ], String::from("strip"));
+
+        let mut mesh = create_mesh();
+        mesh.weld_vertices(0.01);
+        let triangles = mesh.get_triangles();
+        assert_eq!(triangles[0].get_vertices()[1], triangles[1].get_vertices()[0]);
+        assert_eq!(*triangles[1].get_vertices()[2], Vector3::new(0.03, 0., 0.));
+
+        let mut mesh = create_mesh();
+        mesh.weld_vertices(0.);
+        assert_eq!(*mesh.get_triangles()[1].get_vertices()[0], Vector3::new(0.0051, 1., 0.));
+
+        // tangent follows u texture coordinate and stays perpendicular to normal
+        let tangent = mesh.compute_tangents()[0][0];
+        assert!(tangent.dot(&mesh.get_triangles()[0].get_normal()).abs() < 0.0001);
+        assert!(tangent.y > 0.99);
+    }
+
+    #[test]
+    fn analytic_primitives() {
+        let ray = Ray::new(Vector3::new(0., 0., 10.), Vector3::new(0., 0., -1.));
+
+        let cuboid = Cuboid::new(Vector3::new(0., 0., 0.), Vector3::new(2., 2., 2.), Material::new());
+        let hit = cuboid.intersects(&ray).unwrap();
+        assert!((hit.distance - 9.).abs() < 0.0001);
+        assert_eq!(hit.normal, Vector3::new(0., 0., 1.));
+
+        let rotation = Quaternion::from_axis_angle(&Vector3::new(0., 1., 0.), std::f32::consts::PI / 4.);
+        let oriented = Cuboid::oriented(Vector3::new(0., 0., 0.), Vector3::new(2., 2., 2.), &rotation, Material::new());
+        assert!((oriented.intersects(&ray).unwrap().distance - (10. - f32::sqrt(2.))).abs() < 0.0001);
+        assert!((oriented.get_bbox().unwrap().max.x - f32::sqrt(2.)).abs() < 0.0001);
+
+        let cylinder = Cylinder::new(Vector3::new(0., 0., -1.), Vector3::new(0., 0., 1.), 0.5, Material::new());
+        let hits = cylinder.get_intersections(&ray);
+        assert_eq!(hits.len(), 2);
+        assert!((hits[0].distance - 9.).abs() < 0.0001 && (hits[1].distance - 11.).abs() < 0.0001);
+
+        let side_ray = Ray::new(Vector3::new(10., 0., 0.), Vector3::new(-1., 0., 0.));
+        let side_hit = cylinder.intersects(&side_ray).unwrap();
+        assert!((side_hit.distance - 9.5).abs() < 0.0001);
+        assert!((side_hit.normal.x - 1.).abs() < 0.0001);
+
+        let torus = Torus::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 1.), 2., 0.5, Material::new());
+        assert!(torus.intersects(&ray).is_none());
+        let torus_hits = torus.get_intersections(&side_ray);
+        assert_eq!(torus_hits.len(), 4);
+        assert!((torus_hits[0].distance - 7.5).abs() < 0.001);
+
+        let capsule = Capsule::new(Vector3::new(0., -1., 0.), Vector3::new(0., 1., 0.), 0.5, Material::new());
+        let down_ray = Ray::new(Vector3::new(0., 10., 0.), Vector3::new(0., -1., 0.));
+        let capsule_hit = capsule.intersects(&down_ray).unwrap();
+        assert!((capsule_hit.distance - 8.5).abs() < 0.0001);
+        assert!((capsule_hit.normal.y - 1.).abs() < 0.0001);
+        assert!((capsule.get_bbox().unwrap().max.y - 1.5).abs() < 0.0001);
+
+        let rectangle = Rectangle::new(Vector3::new(-1., -1., 0.), Vector3::new(2., 0., 0.), Vector3::new(0., 2., 0.), Material::new());
+        let rectangle_hit = rectangle.intersects(&ray).unwrap();
+        assert!((rectangle_hit.distance - 10.).abs() < 0.0001);
+        assert!((rectangle_hit.normal.z.abs() - 1.).abs() < 0.0001);
+        assert!(rectangle.intersects(&Ray::new(Vector3::new(1.5, 0., 10.), Vector3::new(0., 0., -1.))).is_none());
+
+        let disk = Disk::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 1.), 1., Material::new());
+        assert!((disk.intersects(&ray).unwrap().distance - 10.).abs() < 0.0001);
+        assert!(disk.intersects(&Ray::new(Vector3::new(0.8, 0.8, 10.), Vector3::new(0., 0., -1.))).is_none());
+
+        let cone = Cone::new(Vector3::new(0., 0., -1.), Vector3::new(0., 0., 1.), 1., Material::new());
+        let cone_hits = cone.get_intersections(&side_ray);
+        assert_eq!(cone_hits.len(), 2);
+        assert!((cone_hits[0].distance - 9.5).abs() < 0.0001 && (cone_hits[1].distance - 10.5).abs() < 0.0001);
+        assert!((cone.intersects(&ray).unwrap().distance - 9.).abs() < 0.0001);
+
+        // ray parallel to side of the cone crosses it once
+        assert_eq!(solve_quadratic(0., 2., -4.), vec![2.]);
+        let cone = Cone::new(Vector3::new(0., 0., 0.), Vector3::new(0., 0., 1.), 1., Material::new());
+        let parallel_hits = cone.get_intersections(&Ray::new(Vector3::new(-1., 0., 1.5), Vector3::new(1., 0., -1.)));
+        assert_eq!(parallel_hits.len(), 2);
+        assert!((parallel_hits[0].distance - 0.75).abs() < 0.0001);
+    }
+
+    #[test]
+    fn csg_operations() {
+        let sphere: Arc<dyn Renderable + Send> = Arc::new(Sphere::new(1., Vector3::new(0., 0., 0.), Material::new()));
+        let drill: Arc<dyn Renderable + Send> = Arc::new(Cylinder::new(Vector3::new(0., 0., -2.), Vector3::new(0., 0., 2.), 0.5, Material::new()));
+
+        let drilled = Csg::difference(sphere.clone(), drill.clone());
+        let along_hole = Ray::new(Vector3::new(0., 0., 10.), Vector3::new(0., 0., -1.));
+        assert!(drilled.intersects(&along_hole).is_none());
+
+        let through_wall = Ray::new(Vector3::new(10., 0., 0.), Vector3::new(-1., 0., 0.));
+        let hits = drilled.get_intersections(&through_wall);
+        assert_eq!(hits.len(), 4);
+        assert!((hits[0].distance - 9.).abs() < 0.0001 && (hits[1].distance - 9.5).abs() < 0.0001);
+        assert!((hits[1].normal.x + 1.).abs() < 0.0001);
+
+        let intersection = Csg::intersection(sphere.clone(), drill.clone());
+        let hits = intersection.get_intersections(&along_hole);
+        assert_eq!(hits.len(), 2);
+        assert!((hits[0].distance - 9.).abs() < 0.0001 && (hits[1].distance - 11.).abs() < 0.0001);
+        assert!((intersection.intersects(&through_wall).unwrap().distance - 9.5).abs() < 0.0001);
+
+        let union = Csg::union(sphere, drill);
+        let hits = union.get_intersections(&along_hole);
+        assert_eq!(hits.len(), 2);
+        assert!((hits[0].distance - 8.).abs() < 0.0001 && (hits[1].distance - 12.).abs() < 0.0001);
+
+        let from_inside = Ray::new(Vector3::new(0.75, 0., 0.), Vector3::new(1., 0., 0.));
+        assert!((union.intersects(&from_inside).unwrap().distance - 0.25).abs() < 0.0001);
+
+        // distance fields report every crossing, so they work as operands too
+        let bounds = BBox::new(Vector3::new(-1.5, -1.5, -1.5), Vector3::new(1.5, 1.5, 1.5));
+        let field: Arc<dyn Renderable + Send> = Arc::new(Sdf::new(|point: &Vector3<f32>| sdf::sphere(point, 1.), bounds, Material::new()));
+        let hollow: Arc<dyn Renderable + Send> = Arc::new(Sphere::new(0.5, Vector3::new(0., 0., 0.), Material::new()));
+
+        let hits = Csg::difference(field, hollow).get_intersections(&along_hole);
+        assert_eq!(hits.len(), 4);
+
+        for (hit, expected) in hits.iter().zip([9., 9.5, 10.5, 11.].iter()) {
+            assert!((hit.distance - expected).abs() < 0.001);
+        }
+
+        assert!(hits[0].normal.z > 0.99 && hits[3].normal.z < -0.99);
+    }
+
+    #[test]
+    fn sdf_sphere_tracing() {
+        let bounds = BBox::new(Vector3::new(-3., -2., -2.), Vector3::new(3., 2., 2.));
+        let blob = Sdf::new(|point: &Vector3<f32>| {
+            let left = sdf::sphere(&(point - &Vector3::new(-1., 0., 0.)), 1.);
+            let right = sdf::sphere(&(point - &Vector3::new(1., 0., 0.)), 1.);
+
+            sdf::smooth_min(left, right, 0.5)
+        }, bounds, Material::new());
+
+        let ray = Ray::new(Vector3::new(-1., 0., 10.), Vector3::new(0., 0., -1.));
+        let hit = blob.intersects(&ray).unwrap();
+        assert!((hit.distance - 9.).abs() < 0.001);
+        assert!((hit.normal.z - 1.).abs() < 0.01);
+
+        let seam = Ray::new(Vector3::new(0., 0., 10.), Vector3::new(0., 0., -1.));
+        assert!(blob.intersects(&seam).unwrap().distance < 10.);
+        assert!(blob.intersects(&Ray::new(Vector3::new(0., 5., 10.), Vector3::new(0., 0., -1.))).is_none());
+
+        let repeated = sdf::repeat(&Vector3::new(4.5, 0., 0.), &Vector3::new(2., 0., 0.));
+        assert!((repeated.x - 0.5).abs() < 0.0001);
+        assert!((sdf::cuboid(&Vector3::new(2., 0., 0.), &Vector3::new(1., 1., 1.)) - 1.).abs() < 0.0001);
+        assert!((sdf::torus(&Vector3::new(3., 0., 0.), 2., 0.5) - 0.5).abs() < 0.0001);
+
+        // surfaces far from origin are still hit despite lower float precision
+        let far_bounds = BBox::new(Vector3::new(-1., -1., -501.), Vector3::new(1., 1., -499.));
+        let far = Sdf::new(|point: &Vector3<f32>| sdf::sphere(&(point - &Vector3::new(0., 0., -500.)), 1.), far_bounds, Material::new());
+        let far_hit = far.intersects(&Ray::new(Vector3::new(0.5, 0., 10.), Vector3::new(0., 0., -1.))).unwrap();
+        assert!((far_hit.distance - (510. - f32::sqrt(0.75))).abs() < 0.01);
+        assert!((far_hit.normal.z - f32::sqrt(0.75)).abs() < 0.01);
+    }
+
+    #[test]
+    fn motion_blur_time_sampling() {
+        let mut sphere = Sphere::new(1., Vector3::new(0., 0., 0.), Material::new());
+        sphere.set_position_track(Track::linear(Vector3::new(0., 0., 0.), Vector3::new(4., 0., 0.)));
+
+        let ray_at = |time: f32| Ray::new_with_time(Vector3::new(4., 0., 10.), Vector3::new(0., 0., -1.), time);
+        assert!(sphere.intersects(&ray_at(0.)).is_none());
+        assert!((sphere.intersects(&ray_at(1.)).unwrap().distance - 9.).abs() < 0.0001);
+        assert!((sphere.get_bbox().unwrap().max.x - 5.).abs() < 0.0001);
+
+        let mut motion = TransformTrack::new();
+        motion.translation = Track::linear(Vector3::new(0., 0., 0.), Vector3::new(4., 0., 0.));
+        let cube: Arc<dyn Renderable + Send> = Arc::new(Cuboid::new(Vector3::new(0., 0., 0.), Vector3::new(2., 2., 2.), Material::new()));
+        let instance = Instance::animated(cube, motion);
+
+        assert!(instance.intersects(&ray_at(0.)).is_none());
+        assert!((instance.intersects(&ray_at(0.75)).unwrap().distance - 9.).abs() < 0.0001);
+        assert!((instance.get_bbox().unwrap().max.x - 5.).abs() < 0.0001);
+
+        let mut camera = Camera::new(60., Vector3::new(0., 0., 5.), Vector3::new(0., 0., 0.));
+        camera.set_shutter(0.25, 0.5);
+        camera.set_position_track(Track::linear(Vector3::new(0., 0., 5.), Vector3::new(10., 0., 5.)));
+
+        for _ in 0..16 {
+            let ray = camera.get_camera_ray(50, 50, 101, 101);
+            assert!(ray.time >= 0.25 && ray.time <= 0.5);
+            assert!((ray.origin.x - ray.time * 10.).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn keyframe_animation_sequence() {
+        let mut track = Track::new();
+        track.add_keyframe_with_interpolation(0., 0., Interpolation::EaseInOut);
+        track.add_keyframe_with_interpolation(2., 10., Interpolation::Step);
+        track.add_keyframe(4., 20.);
+
+        assert_eq!(track.sample(-1.).unwrap(), 0.);
+        assert!((track.sample(0.5).unwrap() - 1.5625).abs() < 0.0001);
+        assert_eq!(track.sample(1.).unwrap(), 5.);
+        assert_eq!(track.sample(3.).unwrap(), 10.);
+        assert_eq!(track.sample(5.).unwrap(), 20.);
+
+        let mut scene = Scene::new(Color::new(0., 0., 0.));
+        let mut light = Light::new(Color::new(255., 255., 255.), 1., Vector3::new(0., 10., 0.));
+        let mut animation = LightAnimation::new();
+        animation.intensity = Track::linear(0., 2.);
+        light.set_animation(animation);
+        scene.add_light(light);
+        scene.add_renderable(Box::new(Sphere::new(1., Vector3::new(0., 0., 0.), Material::new())));
+
+        let mut camera = Camera::new(60., Vector3::new(0., 0., 5.), Vector3::new(0., 0., 0.));
+        camera.set_position_track(Track::linear(Vector3::new(0., 0., 5.), Vector3::new(5., 0., 5.)));
+
+        let mut sequence = SequenceRenderer::new(Renderer::new(8, 8, camera, scene));
+        sequence.set_frame_rate(2.);
+        sequence.set_samples_per_frame(1);
+        sequence.set_shutter(0.5);
+
+        let directory = std::env::temp_dir().join("pathtracer_sequence_test");
+        let paths = sequence.render_sequence(0, 2, directory.to_str().unwrap()).unwrap();
+
+        assert_eq!(paths.len(), 3);
+        assert!(paths[2].ends_with("frame_0002.png"));
+        assert!(paths.iter().all(|path| std::path::Path::new(path).exists()));
+        assert!((sequence.get_renderer().get_render_camera().get_position().x - 5.).abs() < 0.0001);
+        assert_eq!(sequence.get_renderer().get_render_camera().get_shutter(), (1., 1.25));
+    }
+
+    #[test]
+    fn participating_media() {
+        let fog = Medium::new_homogeneous(0.1, 0.4, Color::new(255., 255., 255.), 0.);
+        let ray = Ray::new(Vector3::new(0., 0., 0.), Vector3::new(1., 0., 0.));
+
+        assert!((fog.get_transmittance(&ray, 2.) - f32::exp(-1.)).abs() < 0.0001);
+        assert!((fog.get_albedo() - 0.8).abs() < 0.0001);
+        assert!((fog.get_phase(0.3) - 1. / (4. * std::f32::consts::PI)).abs() < 0.0001);
+
+        let forward = Medium::new_homogeneous(0., 1., Color::new(255., 255., 255.), 0.8);
+        let mean_cosine: f32 = (0..2000).map(|_| forward.sample_phase(&ray.direction, rand::random(), rand::random()).x).sum::<f32>() / 2000.;
+        assert!((mean_cosine - 0.8).abs() < 0.05);
+
+        let bounds = BBox::new(Vector3::new(0., -1., -1.), Vector3::new(2., 1., 1.));
+        let grid = DensityGrid::from_fn(bounds, (4, 4, 4), |point| if point.x < 1. { 1. } else { 0. });
+        assert_eq!(grid.sample(&Vector3::new(0.25, 0., 0.)), 1.);
+        assert_eq!(grid.sample(&Vector3::new(1.75, 0., 0.)), 0.);
+        assert_eq!(grid.sample(&Vector3::new(5., 0., 0.)), 0.);
+
+        let smoke = Medium::new_heterogeneous(0., 1., Color::new(255., 255., 255.), 0., grid);
+        let shifted = Ray::new(Vector3::new(-1., 0., 0.), Vector3::new(1., 0., 0.));
+        let estimate: f32 = (0..2000).map(|_| smoke.get_transmittance(&shifted, 10.)).sum::<f32>() / 2000.;
+        assert!((estimate - f32::exp(-1.)).abs() < 0.05);
+
+        for _ in 0..100 {
+            if let Some(distance) = smoke.sample_distance(&shifted, 10.) {
+                assert!(distance > 1. && distance < 2.5);
+            }
+        }
+    }
+
+    #[test]
+    fn subsurface_scattering() {
+        let subsurface = Subsurface::new(Color::new(0., 204., 255.), [0.1, 0.5, 1.]);
+
+        assert!(subsurface.get_single_scattering_albedo(0).abs() < 0.001);
+        let green = subsurface.get_single_scattering_albedo(1);
+        assert!(green > 0.8 && green < 1.);
+        assert!((subsurface.get_extinction(2) - 1.).abs() < 0.0001);
+
+        let mut material = Material::new();
+        material.reflectivity = 0.;
+        material.subsurface = Some(subsurface);
+
+        let mut renderer = Renderer::new(16, 64, create_sphere_camera(), create_sphere_scene(material, 1., 10.));
+
+        for _ in 0..8 {
+            renderer.render();
+        }
+
+        let image = renderer.get_image();
+        let red: f32 = image.iter().step_by(3).sum();
+        let blue: f32 = image.iter().skip(2).step_by(3).sum();
+
+        assert!(blue > red);
+    }
+
+    #[test]
+    fn bidirectional_integrator() {
+        let mut material = Material::new();
+        material.reflectivity = 0.;
+
+        let intersection_data = IntersectionData { distance: 1., normal: Vector3::new(0., 0., 1.), uv: (0., 0.), vertex_color: None };
+        let bsdf = Bsdf::new(&material, &intersection_data);
+        let wo = Vector3::new(0., 0., 1.);
+        let sample = bsdf.sample(&wo, 0.5, 0.25, 0.5).unwrap();
+
+        assert!(!sample.delta && sample.direction.z > 0.);
+        assert!((sample.pdf - bsdf.pdf(&wo, &sample.direction)).abs() < 0.0001);
+        assert_eq!(bsdf.pdf(&wo, &Vector3::new(0., 0., -1.)), 0.);
+
+        let scene = create_sphere_scene(material, 10., 5.);
+        let bidirectional = BidirectionalIntegrator::new();
+        let mut radiance = 0.;
+
+        for _ in 0..64 {
+            let ray = Ray::new(Vector3::new(0., 0., 5.), Vector3::new(0., 0., -1.));
+            radiance += bidirectional.get_radiance(ray, &scene, &mut RandomSampler::new()).r / 64.;
+        }
+
+        // albedo / pi * intensity * cos / distance^2
+        let expected = 255. / std::f32::consts::PI * 10. / 16.;
+        assert!((radiance - expected).abs() < expected * 0.05);
+
+        let missed = Ray::new(Vector3::new(0., 0., 5.), Vector3::new(0., 1., 0.));
+        assert_eq!(bidirectional.get_radiance(missed, &scene, &mut RandomSampler::new()).r, 0.);
+        let missed = Ray::new(Vector3::new(0., 0., 5.), Vector3::new(0., 1., 0.));
+        assert_eq!(PathIntegrator::new().get_radiance(missed, &scene, &mut RandomSampler::new()).r, 0.);
+
+        let mut renderer = Renderer::new(8, 8, create_sphere_camera(), scene);
+        renderer.set_integrator(Box::new(bidirectional));
+        renderer.render();
+
+        assert!(renderer.get_image().iter().any(|value| *value > 0.));
+    }
+
+    #[test]
+    fn photon_mapping_caustics() {
+        let photons: Vec<Photon> = (0..200).map(|i| {
+            let position = Vector3::new((i % 10) as f32, ((i / 10) % 5) as f32, (i / 50) as f32);
+            Photon::new(position, Vector3::new(0., 1., 0.), Color::new(1., 1., 1.))
+        }).collect();
+        let photon_map = PhotonMap::new(photons);
+        let center = Vector3::new(4.2, 2.1, 1.9);
+
+        let expected = photon_map.get_photons().iter().filter(|photon| (&photon.position - &center).magnitude() <= 1.5).count();
+        assert_eq!(photon_map.len(), 200);
+        assert_eq!(photon_map.find_in_radius(&center, 1.5).len(), expected);
+
+        let mut glass = Material::new();
+        glass.reflectivity = 0.;
+        glass.opacity = 0.;
+        glass.refractive_index = 1.5;
+
+        let mut floor = Material::new();
+        floor.reflectivity = 0.;
+
+        let mut scene = Scene::new(Color::new(0., 0., 0.));
+        scene.add_light(Light::new(Color::new(255., 255., 255.), 10., Vector3::new(0., 10., 0.)));
+        scene.add_renderable(Box::new(Sphere::new(1., Vector3::new(0., 2.5, 0.), glass)));
+        scene.add_renderable(Box::new(Plane::new(Vector3::new(0., 0., 0.), floor, Vector3::new(0., 1., 0.))));
+
+        let mut integrator = PhotonMapIntegrator::new(0.2);
+        integrator.set_photons_per_pass(20000);
+        integrator.prepare(&scene);
+        integrator.prepare(&scene);
+
+        assert!(integrator.get_radius() < 0.2);
+        assert!(!integrator.get_photon_map().is_empty());
+
+        let caustic = integrator.get_radiance(Ray::new(Vector3::new(0., 0.5, 0.), Vector3::new(0., -1., 0.)), &scene, &mut RandomSampler::new());
+        let lit = integrator.get_radiance(Ray::new(Vector3::new(3., 0.5, 0.), Vector3::new(0., -1., 0.)), &scene, &mut RandomSampler::new());
+
+        assert!(caustic.r > lit.r);
+    }
+
+    #[test]
+    fn metropolis_light_transport() {
+        let mut sampler = MetropolisSampler::new(0., 0., 2);
+        let initial = [sampler.next_1d(), sampler.next_1d()];
+
+        // small steps without spread keep primary samples
+        sampler.start_iteration();
+        sampler.start_stream(0);
+        assert_eq!([sampler.next_1d(), sampler.next_1d()], initial);
+        sampler.accept();
+
+        let mut sampler = MetropolisSampler::new(0.01, 1., 2);
+        let initial = sampler.next_1d();
+        sampler.start_iteration();
+        assert!(sampler.is_large_step());
+        assert!(sampler.next_1d() != initial);
+        sampler.reject();
+
+        let mut material = Material::new();
+        material.reflectivity = 0.;
+
+        let mut metropolis = MetropolisIntegrator::new();
+        metropolis.set_bootstrap_samples(2000);
+        metropolis.set_mutations_per_pixel(64);
+
+        let mut renderer = Renderer::new(8, 8, create_sphere_camera(), create_sphere_scene(material, 10., 5.));
+        renderer.set_integrator(Box::new(metropolis));
+
+        for _ in 0..4 {
+            renderer.render();
+        }
+
+        // sphere covers middle of the image, light behind camera leaves corners black
+        let image = renderer.get_image();
+        let corner: f32 = image[0..3].iter().sum();
+        let sphere: f32 = (3..5).flat_map(|y| (3..5).map(move |x| y * 8 + x)).map(|pixel| image[pixel * 3..pixel * 3 + 3].iter().sum::<f32>()).sum::<f32>() / 4.;
+
+        assert_eq!(corner, 0.);
+        assert!(sphere > 0.);
+
+        // close to albedo / pi * intensity / distance^2 of sphere point facing the light
+        let expected = 255. / std::f32::consts::PI * 10. / 16. * 3.;
+        assert!((sphere - expected).abs() < expected * 0.25);
+    }
+
+    #[test]
+    fn path_guiding() {
+        let mut guide = PathGuide::new(BBox::new(Vector3::new(-1., -1., -1.), Vector3::new(1., 1., 1.)), 2, 8);
+        let point = Vector3::new(0.5, 0.5, 0.5);
+        let up = Vector3::new(0., 0., 1.);
+
+        for _ in 0..100 {
+            guide.record(&point, &up, 1.);
+        }
+
+        guide.update();
+
+        assert!(guide.is_trained(&point));
+        assert!(!guide.is_trained(&Vector3::new(-0.5, -0.5, -0.5)));
+        assert!(guide.sample(&Vector3::new(-0.5, -0.5, -0.5), 0.5, 0.5, 0.5).is_none());
+        assert!(guide.pdf(&point, &up) > 4. / (4. * std::f32::consts::PI));
+
+        let upward = (0..100).filter(|_| guide.sample(&point, rand::random(), rand::random(), rand::random()).unwrap().0.z > 0.7).count();
+        assert!(upward > 50);
+
+        // guided and unguided renders converge to the same image
+        let render = |guided: bool| {
+            let mut material = Material::new();
+            material.reflectivity = 0.;
+
+            let mut scene = create_sphere_scene(material.clone(), 1., 10.);
+            scene.add_renderable(Box::new(Cuboid::new(Vector3::new(0., 0., -1.75), Vector3::new(6., 6., 0.5), material)));
+
+            let mut renderer = Renderer::new(16, 16, create_sphere_camera(), scene);
+            renderer.set_path_guiding(guided);
+
+            for _ in 0..32 {
+                renderer.render();
+            }
+
+            assert!(renderer.get_image().iter().all(|value| value.is_finite()));
+            renderer.get_image().clone()
+        };
+
+        let mean = |image: &Vec<f32>| image.iter().sum::<f32>() / image.len() as f32;
+        let guided = render(true);
+        let unguided = render(false);
+
+        assert!(mean(&unguided) > 0.);
+        assert!((mean(&guided) - mean(&unguided)).abs() < mean(&unguided) * 0.05);
+    }
+
+    #[test]
+    fn light_tree_resampling() {
+        let lights: Vec<Light> = (0..16).map(|i| Light::new(Color::new(255., 255., 255.), 1., Vector3::new(i as f32 * 2., 0., 0.))).collect();
+        let tree = LightTree::new(&lights);
+        let point = Vector3::new(0., 1., 0.);
+
+        let mut probabilities = vec![0.; lights.len()];
+
+        for i in 0..10000 {
+            let (index, probability) = tree.sample(&lights, &point, (i as f32 + 0.5) / 10000.).unwrap();
+            probabilities[index] = probability;
+        }
+
+        assert!((probabilities.iter().sum::<f32>() - 1.).abs() < 0.01);
+        assert!(probabilities[0] > probabilities[15]);
+
+        let mut reservoir = Reservoir::new();
+        reservoir.update(3, 2., 1., 1., 0.5);
+        reservoir.update(5, 0., 0., 1., 0.5);
+        assert_eq!(reservoir.light, 3);
+        assert_eq!(reservoir.get_weight(), 1.);
+
+        // one light picked per shading point converges to the estimate summing all lights
+        let render = |resampled: bool| {
+            let mut material = Material::new();
+            material.reflectivity = 0.;
+
+            let mut scene = Scene::new(Color::new(0., 0., 0.));
+
+            for light in &lights {
+                scene.add_light(light.clone());
+            }
+
+            scene.add_renderable(Box::new(Cuboid::new(Vector3::new(0., 0., -1.75), Vector3::new(40., 40., 0.5), material)));
+
+            let camera = Camera::new(60., Vector3::new(0., 0., 5.), Vector3::new(0., 0., 0.));
+            let mut renderer = Renderer::new(16, 16, camera, scene);
+            renderer.set_resampled_lighting(resampled);
+
+            for _ in 0..64 {
+                renderer.render();
+            }
+
+            assert!(renderer.get_image().iter().all(|value| value.is_finite()));
+            renderer.get_image().iter().sum::<f32>() / renderer.get_image().len() as f32
+        };
+
+        let resampled = render(true);
+        let all_lights = render(false);
+
+        assert!(all_lights > 0.);
+        assert!((resampled - all_lights).abs() < all_lights * 0.1, "{} {}", resampled, all_lights);
+    }
+
+    #[test]
+    fn denoiser() {
+        // noisy flat image keeps its mean and loses variance, edge in normal buffer is preserved
+        let mut guides = GuideBuffers::new(16, 16);
+        let mut image = vec![0.; 16 * 16 * 3];
+
+        for i in 0..16 * 16 {
+            let left = i % 16 < 8;
+            guides.albedo[i * 3..i * 3 + 3].copy_from_slice(&[1., 1., 1.]);
+            guides.normal[i * 3..i * 3 + 3].copy_from_slice(if left { &[0., 0., 1.] } else { &[1., 0., 0.] });
+            guides.depth[i] = 1.;
+
+            let value = if left { 50. } else { 200. } + if i % 2 == 0 { 20. } else { -20. };
+            image[i * 3..i * 3 + 3].copy_from_slice(&[value, value, value]);
+        }
+
+        let denoised = Denoiser::new().denoise(&image, &guides, 16, 16, 1);
+        let left: Vec<f32> = (0..16 * 16).filter(|i| i % 16 < 8).map(|i| denoised[i * 3]).collect();
+        let right: Vec<f32> = (0..16 * 16).filter(|i| i % 16 >= 8).map(|i| denoised[i * 3]).collect();
+
+        assert!(left.iter().all(|value| (value - 50.).abs() < 5.));
+        assert!(right.iter().all(|value| (value - 200.).abs() < 5.));
+
+        let mut material = Material::new();
+        material.reflectivity = 0.;
+
+        let mut renderer = Renderer::new(16, 16, create_sphere_camera(), create_sphere_scene(material, 1., 10.));
+        renderer.set_denoiser(Some(Denoiser::new()));
+        renderer.render();
+
+        let guides = renderer.get_guides().unwrap();
+        assert!((guides.depth[8 * 16 + 8] - 4.).abs() < 0.1);
+        assert!(guides.normal[(8 * 16 + 8) * 3 + 2] > 0.9);
+        assert_ne!(renderer.get_image(), renderer.get_raw_image());
+        assert!(renderer.get_image().iter().all(|value| value.is_finite()));
+    }
+
+    #[test]
+    fn aovs() {
+        let mut material = Material::new();
+        material.reflectivity = 0.3;
+
+        let mut glass = material.clone();
+        glass.opacity = 0.5;
+        glass.emissive_color = Color::new(10., 0., 0.);
+
+        let mut scene = Scene::new(Color::new(40., 40., 60.));
+        scene.add_light(Light::new(Color::new(255., 255., 255.), 1., Vector3::new(2., 3., 4.)));
+        scene.add_renderable(Box::new(Sphere::new(1., Vector3::new(0., 0., 0.), glass)));
+        scene.add_renderable(Box::new(Cuboid::new(Vector3::new(0., 0., -1.75), Vector3::new(6., 6., 0.5), material)));
+
+        let camera = Camera::new(30., Vector3::new(0., 0., 5.), Vector3::new(0., 0., 0.));
+        let mut renderer = Renderer::new(16, 16, camera, scene);
+        renderer.set_aovs(&Aov::all());
+
+        for _ in 0..2 {
+            renderer.render();
+        }
+
+        // lighting passes add up to beauty image
+        let aovs = renderer.get_aovs().unwrap();
+        let passes = [Aov::DiffuseDirect, Aov::DiffuseIndirect, Aov::Specular, Aov::Emission];
+
+        for i in 0..16 * 16 * 3 {
+            let sum: f32 = passes.iter().map(|aov| aovs.get(*aov).unwrap()[i]).sum();
+            assert!((sum - renderer.get_image()[i]).abs() < 0.01);
+        }
+
+        let center = 8 * 16 + 8;
+        assert!((aovs.get(Aov::Depth).unwrap()[center] - 4.).abs() < 0.1);
+        assert_eq!(aovs.get(Aov::ObjectId).unwrap()[center], 1.);
+        assert_eq!(aovs.get(Aov::ObjectId).unwrap()[0], 2.);
+        assert_eq!(aovs.get(Aov::SampleCount).unwrap()[center], 2.);
+        assert!(aovs.get(Aov::MaterialId).unwrap()[center] > 0.);
+        assert!(aovs.get(Aov::Emission).unwrap()[center * 3] > 0.);
+
+        let path = std::env::temp_dir().join("pathtracer_aovs.exr");
+        let path = path.to_str().unwrap();
+        renderer.save_exr(path).unwrap();
+
+        let bytes = std::fs::read(path).unwrap();
+        let channels = 3 + Aov::all().iter().map(|aov| aov.get_channels()).sum::<usize>();
+
+        assert_eq!(&bytes[0..4], &[0x76, 0x2f, 0x31, 0x01]);
+        assert!(bytes.windows(9).any(|window| window == b"albedo.R\0"));
+        assert!(bytes.len() > 16 * (8 + 8 + channels * 16 * 4));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn adaptive_sampling() {
+        assert!(AdaptiveSampling::get_error(100., 10000., 16) < 0.001);
+        assert!(AdaptiveSampling::get_error(100., 20000., 16) > 0.1);
+
+        // budget covers only planned pixels, equally noisy pixels get one sample each
+        let mut pixel_samples = vec![0; 4];
+        let active = AdaptiveSampling::new(0.05).plan(&[1, 2], &[100.; 4], &[20000.; 4], &[16; 4], &mut pixel_samples);
+        assert_eq!(active, 2);
+        assert_eq!(pixel_samples, vec![0, 1, 1, 0]);
+
+        // flat background converges after minimal samples while lit sphere edge keeps sampling
+        let mut material = Material::new();
+        material.reflectivity = 0.;
+
+        let mut scene = Scene::new(Color::new(40., 40., 60.));
+        scene.add_light(Light::new(Color::new(255., 255., 255.), 1., Vector3::new(2., 3., 4.)));
+        scene.add_renderable(Box::new(Sphere::new(1., Vector3::new(0., 0., 0.), material)));
+
+        let camera = Camera::new(60., Vector3::new(0., 0., 5.), Vector3::new(0., 0., 0.));
+        let mut renderer = Renderer::new(16, 16, camera, scene);
+        renderer.set_adaptive_sampling(Some(AdaptiveSampling::new(0.05)));
+
+        let frames = renderer.render_until_converged(1000);
+        let counts = renderer.get_sample_counts();
+
+        assert!(frames < 1000);
+        assert!(renderer.is_converged());
+        assert_eq!(counts[0], 16);
+        assert!(*counts.iter().max().unwrap() > 16);
+        assert!(renderer.get_image().iter().all(|value| value.is_finite()));
+    }
+
+    #[test]
+    fn render_region_and_progressive_resolution() {
+        let create_renderer = || {
+            let mut scene = Scene::new(Color::new(40., 40., 60.));
+            scene.add_light(Light::new(Color::new(255., 255., 255.), 1., Vector3::new(2., 3., 4.)));
+            scene.add_renderable(Box::new(Sphere::new(1., Vector3::new(0., 0., 0.), Material::new())));
+
+            Renderer::new(16, 16, Camera::new(60., Vector3::new(0., 0., 5.), Vector3::new(0., 0., 0.)), scene)
+        };
+
+        let mut renderer = create_renderer();
+        renderer.set_render_region(Some(RenderRegion::new(4, 4, 8, 20)));
+        assert_eq!(renderer.get_render_region(), Some(RenderRegion::new(4, 4, 8, 12)));
+
+        renderer.render();
+
+        let counts = renderer.get_sample_counts();
+        assert_eq!(counts[0], 0);
+        assert_eq!(counts[4 * 16 + 4], 1);
+        assert_eq!(counts[15 * 16 + 11], 1);
+        assert_eq!(counts[15 * 16 + 12], 0);
+        assert!(renderer.get_image()[0..3].iter().all(|value| *value == 0.));
+
+        // resolution doubles every frame until whole image is rendered
+        let mut renderer = create_renderer();
+        renderer.set_progressive(true);
+
+        for scale in [8, 4, 2, 1].iter() {
+            renderer.render();
+            assert_eq!(renderer.get_preview_scale(), *scale);
+        }
+
+        let counts = renderer.get_sample_counts();
+        assert_eq!(counts[0], 4);
+        assert_eq!(counts[4 * 16 + 4], 3);
+        assert_eq!(counts[2 * 16 + 2], 2);
+        assert_eq!(counts[1], 1);
+
+        // moving camera starts from the lowest resolution again and fills blocks from their sampled pixel
+        renderer.get_render_camera().set_position(Vector3::new(0., 0.5, 5.));
+        renderer.render();
+
+        let image = renderer.get_image();
+        assert_eq!(renderer.get_preview_scale(), 8);
+        assert_eq!(&image[(7 * 16 + 7) * 3..(7 * 16 + 7) * 3 + 3], &image[0..3]);
+        assert_eq!(&image[(9 * 16 + 9) * 3..(9 * 16 + 9) * 3 + 3], &image[(8 * 16 + 8) * 3..(8 * 16 + 8) * 3 + 3]);
+
+        // blocks of unaligned region start at its corner
+        let mut renderer = create_renderer();
+        renderer.set_render_region(Some(RenderRegion::new(4, 4, 8, 12)));
+        renderer.set_progressive(true);
+        renderer.render();
+
+        let counts = renderer.get_sample_counts();
+        assert_eq!(counts[4 * 16 + 4], 1);
+        assert_eq!(counts[12 * 16 + 4], 1);
+        assert_eq!(counts[8 * 16 + 8], 0);
+        assert_eq!(counts.iter().sum::<u32>(), 2);
+
+        let image = renderer.get_image();
+        assert!(image[(4 * 16 + 4) * 3..(4 * 16 + 4) * 3 + 3].iter().any(|value| *value > 0.));
+        assert_eq!(&image[(11 * 16 + 11) * 3..(11 * 16 + 11) * 3 + 3], &image[(4 * 16 + 4) * 3..(4 * 16 + 4) * 3 + 3]);
+        assert_eq!(&image[(15 * 16 + 4) * 3..(15 * 16 + 4) * 3 + 3], &image[(12 * 16 + 4) * 3..(12 * 16 + 4) * 3 + 3]);
+        assert!(image[0..3].iter().all(|value| *value == 0.));
+
+        // preview is denoised with upsampled guides
+        let mut renderer = create_renderer();
+        renderer.set_denoiser(Some(Denoiser::new()));
+        renderer.set_progressive(true);
+        renderer.render();
+
+        let preview = renderer.get_image().clone();
+        assert_eq!(renderer.get_preview_scale(), 8);
+        assert!(preview.iter().all(|value| value.is_finite()));
+        assert!(preview.iter().zip(renderer.get_raw_image().iter()).any(|(denoised, raw)| denoised != raw));
+    }
+
+    #[test]
+    fn accumulation_reset_on_change() {
+        let mut scene = Scene::new(Color::new(40., 40., 60.));
+        scene.add_light(Light::new(Color::new(255., 255., 255.), 1., Vector3::new(2., 3., 4.)));
+        scene.add_renderable(Box::new(Sphere::new(1., Vector3::new(0., 0., 0.), Material::new())));
+
+        let camera = Camera::new(60., Vector3::new(0., 0., 5.), Vector3::new(0., 0., 0.));
+        let mut renderer = Renderer::new(8, 8, camera, scene);
+
+        renderer.render();
+        renderer.render();
+        assert_eq!(renderer.get_sample_counts()[0], 2);
+
+        let version = renderer.get_render_camera().get_version();
+        renderer.get_render_camera().set_fov(40.);
+        assert!(renderer.get_render_camera().get_version() != version);
+        renderer.render();
+        assert_eq!(renderer.get_sample_counts()[0], 1);
+
+        renderer.get_render_camera().set_target(Vector3::new(0.1, 0., 0.));
+        renderer.render();
+        assert_eq!(renderer.get_sample_counts()[0], 1);
+
+        // rays follow the new target
+        let ray = renderer.get_render_camera().get_camera_ray_at(4., 4., 8, 8, 0.);
+        let mut expected = &Vector3::new(0.1, 0., 0.) - &renderer.get_render_camera().get_position();
+        expected.normalize();
+        assert!((&ray.direction - &expected).magnitude() < 0.0001);
+
+        renderer.get_scene_mut().set_light(0, Light::new(Color::new(255., 0., 0.), 2., Vector3::new(2., 3., 4.)));
+        renderer.render();
+        renderer.render();
+        assert_eq!(renderer.get_sample_counts()[0], 2);
+
+        renderer.get_scene_mut().get_material_mut(0).unwrap().diffuse_color = Color::new(0., 255., 0.);
+        renderer.render();
+        assert_eq!(renderer.get_sample_counts()[0], 1);
+
+        // missing renderable is not a change
+        renderer.get_scene_mut().get_material_mut(5);
+        renderer.render();
+        assert_eq!(renderer.get_sample_counts()[0], 2);
+
+        // instance edits its own copy of shared material
+        let sphere: Arc<dyn Renderable + Send> = Arc::new(Sphere::new(1., Vector3::new(0., 0., 0.), Material::new()));
+        let mut instance = Instance::new(Arc::clone(&sphere), Matrix4::identity());
+        instance.get_material_mut().unwrap().diffuse_color = Color::new(255., 0., 0.);
+        assert_eq!(instance.get_material().diffuse_color, Color::new(255., 0., 0.));
+        assert_eq!(sphere.get_material().diffuse_color, Color::new(255., 255., 255.));
+    }
+
+    #[test]
+    fn photon_mapping_and_bidirectional_integrators_agree() {
+        let mut material = Material::new();
+        material.reflectivity = 0.;
+        material.diffuse_color = Color::new(128., 128., 128.);
+
+        let mut scene = Scene::new(Color::new(0., 0., 0.));
+        scene.add_light(Light::new(Color::new(255., 255., 255.), 20., Vector3::new(1., 3., 3.)));
+        scene.add_renderable(Box::new(Sphere::new(1., Vector3::new(0., 0., 0.), material.clone())));
+        scene.add_renderable(Box::new(Plane::new(Vector3::new(0., -1., 0.), material, Vector3::new(0., 1., 0.))));
+
+        let mut photon_mapping = PhotonMapIntegrator::new(0.1);
+        photon_mapping.set_photons_per_pass(20000);
+
+        let bidirectional = BidirectionalIntegrator::new();
+        let mut photon_radiance = 0.;
+        let mut bidirectional_radiance = 0.;
+
+        // every pass has new photon map, sphere, floor and shadowed floor next to sphere are compared
+        for _ in 0..16 {
+            photon_mapping.prepare(&scene);
+
+            for target in [Vector3::new(0., 0., 0.), Vector3::new(0., -1., 2.), Vector3::new(-1.5, -1., -1.)].iter() {
+                let mut direction = target - &Vector3::new(0., 1., 6.);
+                direction.normalize();
+
+                for _ in 0..512 {
+                    let ray = Ray::new(Vector3::new(0., 1., 6.), direction);
+                    photon_radiance += photon_mapping.get_radiance(ray, &scene, &mut RandomSampler::new()).r;
+                    let ray = Ray::new(Vector3::new(0., 1., 6.), direction);
+                    bidirectional_radiance += bidirectional.get_radiance(ray, &scene, &mut RandomSampler::new()).r;
+                }
+            }
+        }
+
+        assert!(photon_radiance > 0.);
+        assert!((photon_radiance - bidirectional_radiance).abs() < bidirectional_radiance * 0.1);
+    }
+
+    #[test]
+    fn surface_sampling() {
+        let samples: Vec<(f32, f32)> = (0..64).map(|i| ((i % 8) as f32 / 8. + 0.0625, (i / 8) as f32 / 8. + 0.0625)).collect();
+
+        let rectangle = Rectangle::new(Vector3::new(0., 0., 0.), Vector3::new(2., 0., 0.), Vector3::new(0., 3., 0.), Material::new());
+        for (r1, r2) in &samples {
+            let (point, normal, area) = rectangle.sample_surface(*r1, *r2, 0.).unwrap();
+            assert!(point.z == 0. && point.x >= 0. && point.x <= 2. && point.y >= 0. && point.y <= 3.);
+            assert_eq!(normal, rectangle.get_normal());
+            assert!((area - 6.).abs() < 0.0001);
+        }
+
+        let disk = Disk::new(Vector3::new(0., 0., 1.), Vector3::new(0., 0., 1.), 2., Material::new());
+        for (r1, r2) in &samples {
+            let (point, normal, area) = disk.sample_surface(*r1, *r2, 0.).unwrap();
+            assert!((point.z - 1.).abs() < 0.0001 && point.x * point.x + point.y * point.y <= 4.0001);
+            assert!((normal.z - 1.).abs() < 0.0001);
+            assert!((area - 4. * std::f32::consts::PI).abs() < 0.0001);
+        }
+
+        // faces are picked by area, 6 for x, 3 for y and 2 for z faces, so half of r1 values hit x faces
+        let cuboid = Cuboid::new(Vector3::new(0., 0., 0.), Vector3::new(1., 2., 3.), Material::new());
+        let mut x_faces = 0;
+        for (r1, r2) in &samples {
+            let (point, normal, area) = cuboid.sample_surface(*r1, *r2, 0.).unwrap();
+            assert!((area - 22.).abs() < 0.0001);
+            assert!((point.dot(&normal) - (normal.x.abs() * 0.5 + normal.y.abs() * 1. + normal.z.abs() * 1.5)).abs() < 0.0001);
+            assert!(point.x.abs() <= 0.5001 && point.y.abs() <= 1.0001 && point.z.abs() <= 1.5001);
+
+            if normal.x.abs() > 0.5 {
+                x_faces += 1;
+            }
+        }
+        assert_eq!(x_faces, 32);
+
+        let sphere: Arc<dyn Renderable + Send> = Arc::new(Sphere::new(1., Vector3::new(0., 0., 0.), Material::new()));
+        let mut transform = Matrix4::identity();
+        transform.make_scale(2., 2., 2.);
+        transform.elements[12] = 1.;
+        let instance = Instance::new(sphere, transform);
+        for (r1, r2) in &samples {
+            let (point, normal, area) = instance.sample_surface(*r1, *r2, 0.).unwrap();
+            let offset = &point - &Vector3::new(1., 0., 0.);
+            assert!((offset.magnitude() - 2.).abs() < 0.0001);
+            assert!((normal.magnitude() - 1.).abs() < 0.0001 && normal.dot(&offset) > 0.);
+            assert!((area - 16. * std::f32::consts::PI).abs() < 0.01);
+        }
+
+        // second triangle has three times the area of the first one
+        let mesh = Mesh::new(Material::new(), vec![
+            Triangle::new(Vector3::new(0., 0., 0.), Vector3::new(1., 0., 0.), Vector3::new(0., 2., 0.)),
+            Triangle::new(Vector3::new(0., 0., 1.), Vector3::new(3., 0., 1.), Vector3::new(0., 2., 1.))
+        ], String::from("mesh"));
+        let on_second = samples.iter().filter(|(r1, r2)| mesh.sample_surface(*r1, *r2, 0.).unwrap().0.z == 1.).count();
+        assert!((mesh.get_surface_area() - 4.).abs() < 0.0001);
+        assert_eq!(on_second, 48);
+    }
 }